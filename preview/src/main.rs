@@ -1,58 +1,170 @@
-use pretty_xmlish::{Pretty, PrettyConfig};
+use std::io::Read;
+use std::process::exit;
 
-fn main() {
-    let mut config = PrettyConfig::default();
-    for arg in std::env::args() {
-        if let Some(n) = arg.parse().ok() {
-            config.width = n;
+use pretty_xmlish::{parse::ParseResult, query::Query, Pretty, PrettyConfig};
+
+const USAGE: &str = "\
+Usage: pretty-xmlish [OPTIONS] [FILE]
+
+Renders the tree in FILE, or in the standard input if FILE is `-` or missing.
+
+Options:
+  -f, --from <FORMAT>   json, line (the one-line format) or xml,
+                        guessed from the input if missing
+  -q, --query <QUERY>   only render what the query selects, like
+                        `//BatchScan[@table='t1']`
+  -s, --style <STYLE>   unicode (default), ascii, line, xml, dot (Graphviz)
+                        or html (nested lists, notes as tooltips)
+  -w, --width <N>       preferred width, defaults to $COLUMNS or 120
+  -i, --indent <N>      indentation of the tree, defaults to 4
+  -b, --boundaries      draw a box around the tree
+  -r, --reduced         reduced spaces
+  -h, --help            print this message";
+
+enum Style {
+    Unicode,
+    Ascii,
+    Line,
+    Xml,
+    Dot,
+    Html,
+}
+
+struct Args {
+    from: Option<String>,
+    query: Option<Query>,
+    style: Style,
+    config: PrettyConfig,
+    file: Option<String>,
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("pretty-xmlish: {}", message);
+    exit(2)
+}
+
+/// The value after `flag`, which cannot be missing.
+fn value(flag: &str, value: Option<String>) -> String {
+    value.unwrap_or_else(|| fail(&format!("missing value for {}", flag)))
+}
+
+fn number(flag: &str, value: String) -> usize {
+    (value.parse()).unwrap_or_else(|_| fail(&format!("{} expects a number, got `{}`", flag, value)))
+}
+
+fn parse_args() -> Args {
+    let width = std::env::var("COLUMNS").ok().and_then(|c| c.parse().ok());
+    let mut args = Args {
+        from: None,
+        query: None,
+        style: Style::Unicode,
+        config: PrettyConfig {
+            width: width.unwrap_or(120),
+            need_boundaries: false,
+            ..Default::default()
+        },
+        file: None,
+    };
+    let mut it = std::env::args().skip(1);
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                exit(0)
+            }
+            "-f" | "--from" => args.from = Some(value(&arg, it.next())),
+            "-q" | "--query" => {
+                let query = value(&arg, it.next());
+                let query =
+                    Query::parse(&query).unwrap_or_else(|e| fail(&format!("invalid query: {}", e)));
+                args.query = Some(query);
+            }
+            "-s" | "--style" => {
+                args.style = match value(&arg, it.next()).as_str() {
+                    "unicode" => Style::Unicode,
+                    "ascii" => Style::Ascii,
+                    "line" => Style::Line,
+                    "xml" => Style::Xml,
+                    "dot" => Style::Dot,
+                    "html" => Style::Html,
+                    s => fail(&format!("unknown style `{}`", s)),
+                }
+            }
+            "-w" | "--width" => args.config.width = number(&arg, value(&arg, it.next())),
+            "-i" | "--indent" => args.config.indent = number(&arg, value(&arg, it.next())),
+            "-b" | "--boundaries" => args.config.need_boundaries = true,
+            "-r" | "--reduced" => args.config.reduced_spaces = true,
+            "-" => args.file = None,
+            s if s.starts_with('-') => fail(&format!("unknown option `{}`\n\n{}", s, USAGE)),
+            _ if args.file.is_some() => fail("more than one input file"),
+            _ => args.file = Some(arg),
         }
     }
-    // BatchNestedLoopJoin { type: Inner, predicate: ($0 = ($3 + $4)), output_indices: all }
-    //   BatchExchange { order: [], dist: Single }
-    //     BatchScan { table: t1, columns: [v1, v2, v3] }
-    //   BatchExchange { order: [], dist: Single }
-    //     BatchScan { table: t2, columns: [v1, v2, v3] }
-    let pretty = Pretty::simple_record(
-        "BatchNestedLoopJoin",
-        vec![
-            ("type", "Inner".into()),
-            ("predicate", "($0 = ($3 + $4))".into()),
-            ("output_indices", "all".into()),
-        ],
-        vec![
-            Pretty::simple_record(
-                "BatchExchange",
-                vec![("order", Pretty::Array(vec![])), ("dist", "Single".into())],
-                vec![Pretty::childless_record(
-                    "BatchScan",
-                    vec![
-                        ("table", "t1".into()),
-                        ("columns", Pretty::list_of_strings(&["v1", "v2", "v3"])),
-                    ],
-                )],
-            ),
-            Pretty::simple_record(
-                "BatchExchange",
-                vec![("order", Pretty::Array(vec![])), ("dist", "Single".into())],
-                vec![Pretty::childless_record(
-                    "BatchScan",
-                    vec![
-                        ("table", "t2".into()),
-                        ("columns", Pretty::list_of_strings(&["v1", "v4444444444444444444444444444444444444444444444447777777777777777777777777777772", "v3"])),
-                    ],
-                )],
-            ),
-        ],
-    );
+    args
+}
+
+fn read_input(file: Option<&str>) -> String {
+    let mut src = String::new();
+    let res = match file {
+        Some(path) => std::fs::File::open(path).and_then(|mut f| f.read_to_string(&mut src)),
+        None => std::io::stdin().read_to_string(&mut src),
+    };
+    if let Err(e) = res {
+        fail(&format!(
+            "cannot read {}: {}",
+            file.unwrap_or("the standard input"),
+            e
+        ));
+    }
+    src
+}
+
+fn parse<'s>(from: Option<&str>, src: &'s str) -> ParseResult<Pretty<'s>> {
+    match from {
+        Some("json") => Pretty::parse_json(src),
+        Some("line") => Pretty::parse_one_line(src.trim_end()),
+        Some("xml") => Pretty::parse_xml(src),
+        Some(f) => fail(&format!("unknown input format `{}`", f)),
+        None => match src.trim_start().chars().next() {
+            Some('<') => Pretty::parse_xml(src),
+            // Arrays of the one-line format also start with `[`
+            Some('{' | '[') => Pretty::parse_json(src)
+                .or_else(|e| Pretty::parse_one_line(src.trim_end()).map_err(|_| e)),
+            _ => Pretty::parse_one_line(src.trim_end()),
+        },
+    }
+}
+
+fn render(style: &Style, config: &mut PrettyConfig, pretty: &Pretty) -> String {
     let mut out = String::new();
-    let w = config.unicode(&mut out, &pretty);
-    out.push('\n');
-    config.need_boundaries = false;
-    config.reduced_spaces = true;
-    config.unicode(&mut out, &pretty);
-    // config.ascii(&mut out, &pretty);
-    out.push('\n');
-    config.indent = 3;
-    config.unicode(&mut out, &pretty);
-    println!("{}\nActual width: {}", out, w);
+    match style {
+        Style::Unicode => {
+            config.unicode(&mut out, pretty);
+        }
+        Style::Ascii => config.ascii(&mut out, pretty),
+        Style::Line => config.one_line(&mut out, pretty),
+        Style::Xml => config.xml(&mut out, pretty),
+        Style::Dot => config.dot(&mut out, pretty),
+        Style::Html => {
+            out.push_str("<!DOCTYPE html>\n<meta charset=\"utf-8\">\n");
+            config.html(&mut out, pretty);
+        }
+    }
+    out.truncate(out.trim_end_matches('\n').len());
+    out
+}
+
+fn main() {
+    let args = parse_args();
+    let src = read_input(args.file.as_deref());
+    let pretty = parse(args.from.as_deref(), &src).unwrap_or_else(|e| fail(&e.to_string()));
+    let selected = match &args.query {
+        Some(query) => query.select(&pretty).into_iter().map(|(_, p)| p).collect(),
+        None => vec![&pretty],
+    };
+    for p in selected {
+        // `unicode` shrinks the width to what it used
+        let mut config = args.config.clone();
+        println!("{}", render(&args.style, &mut config, p));
+    }
 }
@@ -0,0 +1,118 @@
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+const JOIN: &str = r#"{ "name": "Join", "on": "a = b", "children": [
+  { "name": "Scan", "table": "t1" },
+  { "name": "Scan", "table": "t2" }
+] }"#;
+
+/// Runs the binary with `input` on the standard input.
+fn run(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_pretty-xmlish"))
+        .args(args)
+        .env_remove("COLUMNS")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // The binary does not read the input when the arguments are wrong
+    let _ = child.stdin.take().unwrap().write_all(input.as_bytes());
+    child.wait_with_output().unwrap()
+}
+
+/// The standard output of a successful run.
+fn stdout(args: &[&str], input: &str) -> String {
+    let out = run(args, input);
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(out.status.success(), "{:?}: {}", args, stderr);
+    String::from_utf8(out.stdout).unwrap()
+}
+
+const TREE: &str = "\
+Join { on: a = b }
+├── Scan
+│   └── table: t1
+└── Scan
+    └── table: t2
+";
+
+#[test]
+fn input_formats() {
+    assert_eq!(stdout(&["-w", "20"], JOIN), TREE);
+    assert_eq!(stdout(&["-f", "json", "-w", "20"], JOIN), TREE);
+    let xml = stdout(&["-s", "xml"], JOIN);
+    assert_eq!(stdout(&["-w", "20"], &xml), TREE);
+    assert_eq!(stdout(&["--from", "xml", "-w", "20"], &xml), TREE);
+    let line = "Scan { table: t1, cols: [ a, b ] }";
+    assert_eq!(
+        stdout(&["-f", "line", "-s", "line"], line),
+        format!("{}\n", line)
+    );
+    assert_eq!(stdout(&["-s", "line"], line), format!("{}\n", line));
+}
+
+#[test]
+fn styles() {
+    let render = |style| stdout(&["-s", style, "-w", "20"], JOIN);
+    assert_eq!(render("unicode"), TREE);
+    assert_eq!(
+        render("ascii"),
+        "Join {\n    on: a = b\n}\n    Scan {\n        table: t1\n    }\n    Scan {\n        table: t2\n    }\n"
+    );
+    assert_eq!(render("line"), "Join { on: a = b }\n");
+    assert_eq!(
+        render("xml"),
+        "<Join on=\"a = b\">\n    <Scan table=\"t1\"/>\n    <Scan table=\"t2\"/>\n</Join>\n"
+    );
+    let dot = render("dot");
+    assert!(dot.starts_with("digraph {\n"), "{}", dot);
+    assert!(dot.contains("  n0 -> n2;\n"), "{}", dot);
+    let html = render("html");
+    assert!(html.starts_with("<!DOCTYPE html>\n"), "{}", html);
+    assert!(html.contains("<li>table: t2</li>"), "{}", html);
+}
+
+#[test]
+fn options() {
+    assert_eq!(
+        stdout(&["-q", "//Scan[@table='t2']"], JOIN),
+        "Scan { table: t2 }\n"
+    );
+    assert_eq!(
+        stdout(&["-b", "-r", "-i", "2", "-w", "20"], JOIN),
+        "+----------------------+\n\
+         | Join { on: a = b }   |\n\
+         | ├Scan { table: t1 }  |\n\
+         | └Scan { table: t2 }  |\n\
+         +----------------------+\n"
+    );
+    assert!(stdout(&["-h"], "").starts_with("Usage: pretty-xmlish"));
+}
+
+#[test]
+fn errors() {
+    for (args, message) in [
+        (&["-f"][..], "missing value for -f"),
+        (&["--query"], "missing value for --query"),
+        (&["-s"], "missing value for -s"),
+        (&["-w"], "missing value for -w"),
+        (&["-i"], "missing value for -i"),
+        (&["-w", "wide"], "-w expects a number, got `wide`"),
+        (&["-s", "svg"], "unknown style `svg`"),
+        (&["-f", "yaml"], "unknown input format `yaml`"),
+    ] {
+        let out = run(args, JOIN);
+        assert_eq!(out.status.code(), Some(2), "{:?}", args);
+        assert!(out.stdout.is_empty(), "{:?}", args);
+        let stderr = String::from_utf8(out.stderr).unwrap();
+        assert_eq!(
+            stderr,
+            format!("pretty-xmlish: {}\n", message),
+            "{:?}",
+            args
+        );
+    }
+}
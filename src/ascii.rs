@@ -1,24 +1,36 @@
-use crate::{LinedBuffer, Pretty, PrettyConfig, XmlNode};
+use crate::{
+    display_width, elision::ellipsis, key_len, metrics::metrics_width, ref_str, LinedBuffer,
+    Pretty, PrettyConfig, XmlNode,
+};
 
 impl PrettyConfig {
     pub fn ascii(&mut self, out: &mut String, pretty: &Pretty) {
-        let (pretty, width) = self.interesting_ascii(0, pretty, 0, 0);
+        let pretty = self.preprocess(pretty);
+        let reserved = self.reserved_metrics_width(&pretty);
+        self.width = self.width.saturating_sub(reserved);
+        let (pretty, width) = self.interesting_ascii(0, &pretty, 0, 0);
         self.width = width;
         let (pretty, width) = self.interesting_ascii(0, &pretty, 0, 0);
+        let metric_columns = self.metric_columns(&pretty, false);
+        let width = width + metrics_width(&metric_columns);
         let mut dat = LinedBuffer {
             out,
             width,
             config: self,
             already_occupied: 0,
+            metric_columns,
+            pending_metrics: None,
         };
         self.horizon(dat.out, width);
         if self.need_boundaries {
-            dat.out.push_str("\n");
+            dat.out.push('\n');
         }
         dat.begin_line();
         dat.line_ascii(&pretty, 0);
         if self.need_boundaries {
             dat.pusheen();
+        } else {
+            dat.push_metrics();
         }
         self.horizon(dat.out, width);
     }
@@ -37,22 +49,82 @@ impl PrettyConfig {
             return (Pretty::Linearized(pretty, ol_len), len);
         }
         let next_indent = base_indent + self.indent;
+        // The lines of the opening and the closing delimiters once broken,
+        // which share the line if there is nothing in between
+        let delimiters = |open: &str, close: &str, empty: bool| match empty {
+            true => vec![first_line_base + display_width(open) + display_width(close)],
+            false => vec![
+                base_indent + start_add + display_width(open),
+                base_indent + display_width(close) + end_add,
+            ],
+        };
+        // In place of a child or an element, the grid starts on the first line
+        let table_len = |t: &crate::Table| {
+            let indent = if start_add == 0 {
+                base_indent
+            } else {
+                next_indent
+            };
+            (indent + t.width(self.reduced_spaces)).max(first_line_base)
+        };
         use Pretty::*;
         match pretty {
             Text(s) => {
-                let len = s.chars().count() + first_line_base;
+                let len = display_width(s) + first_line_base;
                 (s.as_ref().into(), len)
             }
+            Scalar(s) => {
+                let len = display_width(&s.to_string()) + first_line_base;
+                (pretty.clone(), len)
+            }
             Array(v) => {
+                if let Some(t) = (self.tables).then(|| pretty.to_table()).flatten() {
+                    // A separator after it has a line of its own
+                    let len = table_len(&t);
+                    return (Table(t), len);
+                }
                 let (v, lens): (Vec<_>, Vec<_>) = (v.iter())
                     .map(|p| self.interesting_ascii(next_indent, p, 0, ",".len()))
                     .unzip();
-                let max =
-                    (lens.into_iter().max()).unwrap_or(first_line_base + "[]".len() + end_add);
+                let max = (lens
+                    .into_iter()
+                    .chain(delimiters("[", "]", v.is_empty()))
+                    .max())
+                .unwrap();
                 (Array(v), max)
             }
+            Seq(s) => {
+                let sep = display_width(s.line_sep());
+                let mut lens = vec![];
+                let s = s.map_items(|p| {
+                    let (p, len) = self.interesting_ascii(next_indent, p, 0, sep);
+                    lens.push(len);
+                    p
+                });
+                let max = (lens
+                    .into_iter()
+                    .chain(delimiters(&s.open, &s.close, s.items.is_empty()))
+                    .max())
+                .unwrap();
+                (Seq(s), max)
+            }
+            Map(m) => {
+                let (fields, lens): (Vec<_>, Vec<_>) = (m.iter())
+                    .map(|(k, v)| {
+                        let start = key_len(k, v);
+                        let (f, len) = self.interesting_ascii(next_indent, v, start, ",".len());
+                        ((k.clone(), f), len)
+                    })
+                    .unzip();
+                let max = (lens
+                    .into_iter()
+                    .chain(delimiters("{", "}", m.is_empty()))
+                    .max())
+                .unwrap();
+                (Map(fields.into()), max)
+            }
             Record(xml) => {
-                let header = xml.name.chars().count() + first_line_base + " {".len();
+                let header = display_width(&xml.header()) + first_line_base + " {".len();
                 let (children, c_lens): (Vec<_>, Vec<_>) = (xml.children.iter().enumerate())
                     .map(|(i, p)| {
                         let at_the_end = if i < xml.children.len() - 1 {
@@ -64,20 +136,22 @@ impl PrettyConfig {
                     })
                     .unzip();
                 let (fields, f_lens): (Vec<_>, Vec<_>) = (xml.fields.iter().enumerate())
-                    .map(|(i, (k, v))| {
+                    .zip(self.key_pads(xml))
+                    .map(|((i, (k, v)), pad)| {
                         let end = if i < xml.fields.len() - 1 {
                             ",".len()
                         } else {
                             0
                         };
-                        let start = k.chars().count() + ": ".len();
+                        let start = key_len(k, v) + pad;
                         let (f, len) = self.interesting_ascii(next_indent, v, start, end);
                         ((k.clone(), f), len)
                     })
                     .unzip();
                 let fields_is_linear = len < self.width;
                 let max = (c_lens.into_iter())
-                    .chain(vec![header, "}".len() + end_add].into_iter())
+                    .chain(vec![header, "}".len() + end_add])
+                    .chain(xml.note_lines().map(|n| next_indent + display_width(&n)))
                     .chain(if fields_is_linear {
                         vec![len].into_iter()
                     } else {
@@ -86,64 +160,161 @@ impl PrettyConfig {
                     .max()
                     .unwrap();
                 let xml_node = XmlNode {
-                    name: xml.name.clone(),
-                    fields,
                     fields_is_linear,
-                    children,
+                    ..xml.rebuild(fields.into(), children)
                 };
                 (Record(xml_node), max)
             }
             Linearized(_, l) => (pretty.clone(), *l),
+            Table(t) => (pretty.clone(), table_len(t)),
+            Ref(id) => (
+                pretty.clone(),
+                display_width(&ref_str(*id)) + first_line_base,
+            ),
+            Elided(n) => (
+                pretty.clone(),
+                display_width(&ellipsis(*n)) + first_line_base,
+            ),
         }
     }
 }
 
+impl Pretty<'_> {
+    /// A grid starting on the next line.
+    fn is_table(&self) -> bool {
+        matches!(self, Pretty::Table(..))
+    }
+}
+
 impl<'a> LinedBuffer<'a> {
     pub(crate) fn line_ascii(&mut self, pretty: &Pretty, self_indent_len: usize) {
         let indent_len = self_indent_len + self.config.indent;
         use Pretty::*;
         match pretty {
             Text(s) => self.push(s),
+            Scalar(s) => self.push(&s.to_string()),
+            Ref(id) => self.push(&ref_str(*id)),
+            Elided(n) => self.push(&ellipsis(*n)),
             Array(v) => {
-                self.push("[");
-                if v.is_empty() {
-                    self.push("]");
-                    return;
-                }
-                self.pusheen();
-                for (i, e) in v.iter().enumerate() {
-                    self.begin_line();
-                    self.pip(indent_len);
-                    self.line_ascii(e, indent_len);
-                    if i < v.len() - 1 {
-                        self.push(",");
+                let items = v.iter().map(|p| (None, p)).collect();
+                self.line_ascii_items(items, ("[", ",", "]"), indent_len, self_indent_len);
+            }
+            Map(m) => {
+                let items = m
+                    .iter()
+                    .map(|(k, v)| ((!v.is_elided()).then_some(k.as_ref()), v))
+                    .collect();
+                self.line_ascii_items(items, ("{", ",", "}"), indent_len, self_indent_len);
+            }
+            Seq(s) => {
+                let items = s.items.iter().map(|p| (None, p)).collect();
+                let delimiters = (s.open.as_ref(), s.line_sep(), s.close.as_ref());
+                self.line_ascii_items(items, delimiters, indent_len, self_indent_len);
+            }
+            Record(xml) => self.line_ascii_xml(xml, indent_len, self_indent_len),
+            Table(t) => {
+                // After a key, the grid starts on the next line and is indented.
+                // Otherwise only the indentation is on the line.
+                let after_key = self.already_occupied > self_indent_len;
+                let grid_indent = if after_key {
+                    indent_len
+                } else {
+                    self_indent_len
+                };
+                for (i, line) in t.grid(self.config.reduced_spaces, false).iter().enumerate() {
+                    if i > 0 || after_key {
+                        self.pusheen();
+                        self.begin_line();
+                        self.pip(grid_indent);
                     }
-                    self.pusheen();
+                    self.push(line);
                 }
-                self.begin_line();
-                self.pip(self_indent_len);
-                self.push("]");
             }
-            Record(xml) => self.line_ascii_xml(xml, indent_len, self_indent_len),
             Linearized(p, ol_len) => {
+                self.metrics_of_linearized(p);
                 p.ol_build_str_ascii(self.config.reduced_spaces, self.out);
                 self.already_occupied += ol_len;
             }
         }
     }
 
+    /// Array elements and elided fields have no key. Without a closing delimiter,
+    /// the last item stays on the current line.
+    fn line_ascii_items(
+        &mut self,
+        items: Vec<(Option<&str>, &Pretty)>,
+        (open, sep, close): (&str, &str, &str),
+        indent_len: usize,
+        self_indent_len: usize,
+    ) {
+        self.push(open);
+        if items.is_empty() {
+            self.push(close);
+            return;
+        }
+        self.pusheen();
+        for (i, (k, v)) in items.iter().enumerate() {
+            self.begin_line();
+            self.pip(indent_len);
+            if let Some(k) = k {
+                self.push(k);
+                self.push(":");
+                if !v.is_table() {
+                    self.push(" ");
+                }
+            }
+            self.line_ascii(v, indent_len);
+            let is_last = i == items.len() - 1;
+            if !is_last {
+                self.push_sep(v, sep, indent_len);
+            }
+            if !is_last || !close.is_empty() {
+                self.pusheen();
+            }
+        }
+        if !close.is_empty() {
+            self.begin_line();
+            self.pip(self_indent_len);
+            self.push(close);
+        }
+    }
+
+    /// After the bottom border of a table, the separator has its own line.
+    fn push_sep(&mut self, v: &Pretty, sep: &str, indent_len: usize) {
+        if v.is_table() {
+            self.pusheen();
+            self.begin_line();
+            self.pip(indent_len);
+        }
+        self.push(sep);
+    }
+
     fn line_ascii_xml(&mut self, xml: &XmlNode, indent_len: usize, self_indent_len: usize) {
-        self.push(&xml.name);
+        self.metrics_of(xml);
+        self.push(&xml.header());
         self.push(" {");
         self.pusheen();
-        for (i, (k, v)) in xml.fields.iter().enumerate() {
+        for note in xml.note_lines() {
             self.begin_line();
             self.pip(indent_len);
-            self.push(k);
-            self.push(": ");
+            self.push(&note);
+            self.pusheen();
+        }
+        let pads = self.config.key_pads(xml);
+        for (i, ((k, v), pad)) in xml.fields.iter().zip(pads).enumerate() {
+            self.begin_line();
+            self.pip(indent_len);
+            if !v.is_elided() {
+                self.push(k);
+                self.push(":");
+                if !v.starts_below_key(true) {
+                    self.pip(pad);
+                    self.push(" ");
+                }
+            }
             self.line_ascii(v, indent_len);
             if i < xml.fields.len() - 1 {
-                self.push(",");
+                self.push_sep(v, ",", indent_len);
             }
             self.pusheen();
         }
@@ -0,0 +1,135 @@
+//! Records with optional parts, without juggling vectors.
+//!
+//! ```rust
+//! use pretty_xmlish::Pretty;
+//! let filter: Option<&str> = None;
+//! let plan = Pretty::record("Join")
+//!     .field("type", "Inner")
+//!     .field_opt("filter", filter)
+//!     .child(Pretty::record("Scan").field("table", "t1"))
+//!     .children(["t2", "t3"].map(|t| Pretty::record("Scan").field("table", t).build()))
+//!     .build();
+//! assert_eq!(plan, Pretty::simple_record("Join", vec![("type", "Inner".into())], vec![
+//!     Pretty::childless_record("Scan", vec![("table", "t1".into())]),
+//!     Pretty::childless_record("Scan", vec![("table", "t2".into())]),
+//!     Pretty::childless_record("Scan", vec![("table", "t3".into())]),
+//! ]));
+//! ```
+
+use crate::{Pretty, Str, Weight, XmlNode};
+
+/// See [`Pretty::record`].
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct RecordBuilder<'a> {
+    xml: XmlNode<'a>,
+}
+
+impl<'a> RecordBuilder<'a> {
+    /// A field with an existing key is replaced, see [`crate::FieldMap::insert`].
+    pub fn field(mut self, key: impl Into<Str<'a>>, value: impl Into<Pretty<'a>>) -> Self {
+        self.xml.fields.insert(key.into(), value.into());
+        self
+    }
+
+    /// Only adds the field if there is a value.
+    pub fn field_opt(self, key: impl Into<Str<'a>>, value: Option<impl Into<Pretty<'a>>>) -> Self {
+        match value {
+            Some(value) => self.field(key, value),
+            None => self,
+        }
+    }
+
+    /// Only computes and adds the field if `cond` holds.
+    pub fn field_if<V: Into<Pretty<'a>>>(
+        self,
+        cond: bool,
+        key: impl Into<Str<'a>>,
+        value: impl FnOnce() -> V,
+    ) -> Self {
+        self.field_opt(key, cond.then(value))
+    }
+
+    pub fn fields<K: Into<Str<'a>>, V: Into<Pretty<'a>>>(
+        mut self,
+        fields: impl IntoIterator<Item = (K, V)>,
+    ) -> Self {
+        (self.xml.fields).extend(fields.into_iter().map(|(k, v)| (k, v.into())));
+        self
+    }
+
+    pub fn child(mut self, child: impl Into<Pretty<'a>>) -> Self {
+        self.xml.children.push(child.into());
+        self
+    }
+
+    pub fn child_opt(self, child: Option<impl Into<Pretty<'a>>>) -> Self {
+        match child {
+            Some(child) => self.child(child),
+            None => self,
+        }
+    }
+
+    pub fn child_if<C: Into<Pretty<'a>>>(self, cond: bool, child: impl FnOnce() -> C) -> Self {
+        self.child_opt(cond.then(child))
+    }
+
+    pub fn children<C: Into<Pretty<'a>>>(mut self, children: impl IntoIterator<Item = C>) -> Self {
+        (self.xml.children).extend(children.into_iter().map(Into::into));
+        self
+    }
+
+    /// See [`XmlNode::id`].
+    pub fn id(mut self, id: usize) -> Self {
+        self.xml.id = Some(id);
+        self
+    }
+
+    /// Appends to [`XmlNode::metrics`].
+    pub fn metric(mut self, metric: impl Into<Str<'a>>) -> Self {
+        self.xml.metrics.push(metric.into());
+        self
+    }
+
+    /// See [`XmlNode::weight`].
+    pub fn weight(mut self, weight: f64) -> Self {
+        self.xml.weight = Some(Weight(weight));
+        self
+    }
+
+    /// Appends to [`XmlNode::notes`].
+    pub fn note(mut self, note: impl Into<Str<'a>>) -> Self {
+        self.xml.notes.push(note.into());
+        self
+    }
+
+    pub fn build_node(self) -> XmlNode<'a> {
+        self.xml
+    }
+
+    pub fn build(self) -> Pretty<'a> {
+        Pretty::Record(self.xml)
+    }
+}
+
+impl<'a> From<RecordBuilder<'a>> for Pretty<'a> {
+    fn from(builder: RecordBuilder<'a>) -> Self {
+        builder.build()
+    }
+}
+
+impl<'a> Pretty<'a> {
+    /// Starts a record without fields and children.
+    pub fn record(name: impl Into<Str<'a>>) -> RecordBuilder<'a> {
+        XmlNode::record(name)
+    }
+}
+
+impl<'a> XmlNode<'a> {
+    /// Same as [`Pretty::record`].
+    pub fn record(name: impl Into<Str<'a>>) -> RecordBuilder<'a> {
+        RecordBuilder {
+            xml: XmlNode::new(name.into(), Default::default(), vec![]),
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+};
+
+use crate::{Pretty, XmlNode};
+
+/// Problems with [`Pretty::Ref`]s.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RefError {
+    /// No record has this id.
+    Dangling(usize),
+    /// More than one record has this id.
+    DuplicateId(usize),
+    /// The record with this id refers to itself, possibly indirectly.
+    Cycle(usize),
+}
+
+impl Display for RefError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RefError::Dangling(id) => write!(f, "no record has id #{}", id),
+            RefError::DuplicateId(id) => write!(f, "more than one record has id #{}", id),
+            RefError::Cycle(id) => write!(f, "record #{} refers to itself", id),
+        }
+    }
+}
+
+impl std::error::Error for RefError {}
+
+type Nodes<'p, 'a> = HashMap<usize, &'p XmlNode<'a>>;
+
+fn collect_nodes<'p, 'a>(
+    pretty: &'p Pretty<'a>,
+    nodes: &mut Nodes<'p, 'a>,
+) -> Result<(), RefError> {
+    use Pretty::*;
+    match pretty {
+        Record(xml) => {
+            if let Some(id) = xml.id {
+                if nodes.insert(id, xml).is_some() {
+                    return Err(RefError::DuplicateId(id));
+                }
+            }
+            (xml.fields.iter()).try_for_each(|(_, v)| collect_nodes(v, nodes))?;
+            (xml.children.iter()).try_for_each(|c| collect_nodes(c, nodes))
+        }
+        Array(v) => v.iter().try_for_each(|p| collect_nodes(p, nodes)),
+        Map(m) => m.values().try_for_each(|p| collect_nodes(p, nodes)),
+        Seq(s) => s.items.iter().try_for_each(|p| collect_nodes(p, nodes)),
+        Table(t) => (t.rows.iter().flatten()).try_for_each(|p| collect_nodes(p, nodes)),
+        Linearized(p, _) => collect_nodes(p, nodes),
+        Text(..) | Scalar(..) | Elided(..) | Ref(..) => Ok(()),
+    }
+}
+
+/// The ids a record leads to: references and nested records with an id.
+fn edges(pretty: &Pretty, out: &mut Vec<usize>) {
+    use Pretty::*;
+    match pretty {
+        Record(XmlNode { id: Some(id), .. }) | Ref(id) => out.push(*id),
+        Record(xml) => xml_edges(xml, out),
+        Array(v) => v.iter().for_each(|p| edges(p, out)),
+        Map(m) => m.values().for_each(|p| edges(p, out)),
+        Seq(s) => s.items.iter().for_each(|p| edges(p, out)),
+        Table(t) => t.rows.iter().flatten().for_each(|p| edges(p, out)),
+        Linearized(p, _) => edges(p, out),
+        Text(..) | Scalar(..) | Elided(..) => {}
+    }
+}
+
+fn xml_edges(xml: &XmlNode, out: &mut Vec<usize>) {
+    xml.fields.iter().for_each(|(_, v)| edges(v, out));
+    xml.children.iter().for_each(|c| edges(c, out));
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Visit {
+    InProgress,
+    Done,
+}
+
+fn find_cycle(
+    id: usize,
+    nodes: &Nodes,
+    visits: &mut HashMap<usize, Visit>,
+) -> Result<(), RefError> {
+    match visits.get(&id) {
+        Some(Visit::Done) => return Ok(()),
+        Some(Visit::InProgress) => return Err(RefError::Cycle(id)),
+        None => {}
+    }
+    let xml = nodes.get(&id).ok_or(RefError::Dangling(id))?;
+    visits.insert(id, Visit::InProgress);
+    let mut next = vec![];
+    xml_edges(xml, &mut next);
+    (next.into_iter()).try_for_each(|next| find_cycle(next, nodes, visits))?;
+    visits.insert(id, Visit::Done);
+    Ok(())
+}
+
+impl<'a> Pretty<'a> {
+    /// Records with an id, indexed by the id.
+    pub fn nodes_by_id(&self) -> Result<HashMap<usize, &XmlNode<'a>>, RefError> {
+        let mut nodes = HashMap::new();
+        collect_nodes(self, &mut nodes)?;
+        Ok(nodes)
+    }
+
+    /// Every reference points to exactly one record, and there are no cycles.
+    /// The text renderers never follow references, but [`Pretty::inline_refs`] does.
+    pub fn check_refs(&self) -> Result<(), RefError> {
+        let nodes = self.nodes_by_id()?;
+        let mut roots = vec![];
+        edges(self, &mut roots);
+        let mut visits = HashMap::new();
+        (roots.into_iter().chain(nodes.keys().copied()))
+            .try_for_each(|id| find_cycle(id, &nodes, &mut visits))
+    }
+
+    /// Turns a DAG back into a tree by copying the referred records.
+    /// The ids are dropped, so that nothing gets labelled.
+    pub fn inline_refs(&self) -> Result<Self, RefError> {
+        self.check_refs()?;
+        Ok(self.inline_with(&self.nodes_by_id()?))
+    }
+
+    fn inline_with(&self, nodes: &Nodes<'_, 'a>) -> Self {
+        use Pretty::*;
+        match self {
+            Record(xml) => Record(xml.inline_with(nodes)),
+            Array(v) => Array(v.iter().map(|p| p.inline_with(nodes)).collect()),
+            Map(m) => Map(m.map_values(|p| p.inline_with(nodes))),
+            Seq(s) => Seq(s.map_items(|p| p.inline_with(nodes))),
+            Table(t) => Table(t.map_cells(|p| p.inline_with(nodes))),
+            Ref(id) => Record(nodes[id].inline_with(nodes)),
+            Linearized(p, _) => p.inline_with(nodes),
+            Text(..) | Scalar(..) | Elided(..) => self.clone(),
+        }
+    }
+}
+
+impl<'a> XmlNode<'a> {
+    fn inline_with(&self, nodes: &Nodes<'_, 'a>) -> Self {
+        let fields = (self.fields.iter())
+            .map(|(k, v)| (k.clone(), v.inline_with(nodes)))
+            .collect();
+        let children = self.children.iter().map(|c| c.inline_with(nodes)).collect();
+        XmlNode {
+            id: None,
+            ..self.rebuild(fields, children)
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::mem::discriminant;
+
+use crate::{Pretty, XmlNode};
+
+/// Only records with children are considered as shared subplans,
+/// leaves are cheap to print twice.
+fn is_shareable(xml: &XmlNode) -> bool {
+    !xml.children.is_empty()
+}
+
+/// A record and its structural hash, computed once from the hashes of its
+/// subtrees. Two records are only compared when their hashes are equal.
+#[derive(Clone, Copy)]
+struct Node<'p, 'a> {
+    hash: u64,
+    xml: &'p XmlNode<'a>,
+}
+
+impl Hash for Node<'_, '_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+impl PartialEq for Node<'_, '_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash && (std::ptr::eq(self.xml, other.xml) || self.xml == other.xml)
+    }
+}
+
+impl Eq for Node<'_, '_> {}
+
+#[derive(Default)]
+struct Dedup<'p, 'a> {
+    /// Structural hashes of the shareable records, by address.
+    hashes: HashMap<*const XmlNode<'a>, u64>,
+    counts: HashMap<Node<'p, 'a>, usize>,
+    seen: HashSet<Node<'p, 'a>>,
+    /// Subtrees that are printed as a back-reference at least once.
+    referenced: HashSet<Node<'p, 'a>>,
+    labels: HashMap<Node<'p, 'a>, usize>,
+    /// New ids start after the ones already in the tree.
+    last_id: usize,
+}
+
+impl<'p, 'a> Dedup<'p, 'a> {
+    /// Counts the shareable records in post-order, and returns the
+    /// structural hash of `pretty`, made of the hashes of its subtrees.
+    fn count(&mut self, pretty: &'p Pretty<'a>) -> u64 {
+        use Pretty::*;
+        let mut h = DefaultHasher::new();
+        discriminant(pretty).hash(&mut h);
+        match pretty {
+            Record(xml) => {
+                let XmlNode {
+                    name,
+                    id,
+                    fields,
+                    metrics,
+                    weight,
+                    notes,
+                    fields_is_linear,
+                    children,
+                } = xml;
+                (name, id, metrics, weight, notes, fields_is_linear).hash(&mut h);
+                if let Some(id) = id {
+                    self.last_id = self.last_id.max(*id);
+                }
+                fields.len().hash(&mut h);
+                for (k, v) in fields.iter() {
+                    k.hash(&mut h);
+                    self.count(v).hash(&mut h);
+                }
+                children.len().hash(&mut h);
+                children.iter().for_each(|c| self.count(c).hash(&mut h));
+                if is_shareable(xml) {
+                    let node = Node {
+                        hash: h.finish(),
+                        xml,
+                    };
+                    self.hashes.insert(xml, node.hash);
+                    *self.counts.entry(node).or_default() += 1;
+                }
+            }
+            Array(v) => {
+                v.len().hash(&mut h);
+                v.iter().for_each(|p| self.count(p).hash(&mut h));
+            }
+            Map(m) => {
+                m.len().hash(&mut h);
+                for (k, v) in m.iter() {
+                    k.hash(&mut h);
+                    self.count(v).hash(&mut h);
+                }
+            }
+            Seq(s) => {
+                (&s.open, &s.sep, &s.close, s.items.len()).hash(&mut h);
+                s.items.iter().for_each(|p| self.count(p).hash(&mut h));
+            }
+            Table(t) => {
+                t.columns.hash(&mut h);
+                for row in &t.rows {
+                    row.len().hash(&mut h);
+                    row.iter().for_each(|p| self.count(p).hash(&mut h));
+                }
+            }
+            Text(..) | Scalar(..) | Elided(..) | Linearized(..) | Ref(..) => pretty.hash(&mut h),
+        }
+        h.finish()
+    }
+
+    /// The shareable record `xml` with its hash.
+    fn node(&self, xml: &'p XmlNode<'a>) -> Option<Node<'p, 'a>> {
+        let &hash = self.hashes.get(&(xml as *const _))?;
+        Some(Node { hash, xml })
+    }
+
+    /// The node of `xml` if it appears more than once.
+    fn duplicated(&self, xml: &'p XmlNode<'a>) -> Option<Node<'p, 'a>> {
+        self.node(xml).filter(|node| self.counts[node] > 1)
+    }
+
+    /// A dry run of `rewrite`, because occurrences nested inside a
+    /// back-reference are never printed and should not get a label.
+    fn mark(&mut self, pretty: &'p Pretty<'a>) {
+        use Pretty::*;
+        match pretty {
+            Record(xml) => {
+                if let Some(node) = self.duplicated(xml) {
+                    if !self.seen.insert(node) {
+                        self.referenced.insert(node);
+                        return;
+                    }
+                }
+                xml.fields.iter().for_each(|(_, v)| self.mark(v));
+                xml.children.iter().for_each(|c| self.mark(c));
+            }
+            Array(v) => v.iter().for_each(|p| self.mark(p)),
+            Map(m) => m.values().for_each(|p| self.mark(p)),
+            Seq(s) => s.items.iter().for_each(|p| self.mark(p)),
+            Table(t) => t.rows.iter().flatten().for_each(|p| self.mark(p)),
+            Text(..) | Scalar(..) | Elided(..) | Linearized(..) | Ref(..) => {}
+        }
+    }
+
+    fn rewrite(&mut self, pretty: &'p Pretty<'a>) -> Pretty<'a> {
+        use Pretty::*;
+        match pretty {
+            Record(xml) => {
+                let mut id = xml.id;
+                let node = self.node(xml).filter(|node| self.referenced.contains(node));
+                if let Some(node) = node {
+                    if let Some(&label) = self.labels.get(&node) {
+                        return Ref(label);
+                    }
+                    // Reuse the id if the user has given one.
+                    let label = id.unwrap_or_else(|| {
+                        self.last_id += 1;
+                        self.last_id
+                    });
+                    self.labels.insert(node, label);
+                    id = Some(label);
+                }
+                let fields = (xml.fields.iter())
+                    .map(|(k, v)| (k.clone(), self.rewrite(v)))
+                    .collect();
+                let children = xml.children.iter().map(|c| self.rewrite(c)).collect();
+                Record(XmlNode {
+                    id,
+                    ..xml.rebuild(fields, children)
+                })
+            }
+            Array(v) => Array(v.iter().map(|p| self.rewrite(p)).collect()),
+            Map(m) => Map(m.map_values(|p| self.rewrite(p))),
+            Seq(s) => Seq(s.map_items(|p| self.rewrite(p))),
+            Table(t) => Table(t.map_cells(|p| self.rewrite(p))),
+            Text(..) | Scalar(..) | Elided(..) | Linearized(..) | Ref(..) => pretty.clone(),
+        }
+    }
+}
+
+impl<'a> Pretty<'a> {
+    /// Structurally equal subtrees are printed only once.
+    /// The first occurrence gets an id and is labelled like `Name [#1]`,
+    /// the others are replaced with [`Pretty::Ref`], printed as `→ see #1`.
+    pub fn dedup(&self) -> Self {
+        let mut dedup = Dedup::default();
+        dedup.count(self);
+        dedup.mark(self);
+        dedup.rewrite(self)
+    }
+}
@@ -0,0 +1,264 @@
+use crate::{FieldMap, Pretty, PrettyConfig, Seq, Str, XmlNode};
+
+/// Result of [`Pretty::diff`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Diff<'a> {
+    Same(Pretty<'a>),
+    Added(Pretty<'a>),
+    Removed(Pretty<'a>),
+    /// Replaced by something that cannot be compared, like a text
+    /// replaced by another text, or a record replaced by another record.
+    Changed(Pretty<'a>, Pretty<'a>),
+    /// Records with the same name, compared member-wise.
+    Record(RecordDiff<'a>),
+    /// Arrays, compared by their longest common subsequence.
+    Array(Vec<Diff<'a>>),
+    /// Sequences with the same delimiters, compared like arrays.
+    /// The items of the sequence are empty.
+    Seq(Seq<'a>, Vec<Diff<'a>>),
+    /// Maps, compared key-wise like the fields of a record.
+    Map(Vec<(Str<'a>, Diff<'a>)>),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RecordDiff<'a> {
+    pub name: Str<'a>,
+    /// Fields of the old record in order, then the added ones.
+    pub fields: Vec<(Str<'a>, Diff<'a>)>,
+    /// Children are matched by position.
+    pub children: Vec<Diff<'a>>,
+}
+
+impl<'a> Pretty<'a> {
+    /// What changed from `self` to `new`.
+    pub fn diff(&self, new: &Self) -> Diff<'a> {
+        use Pretty::*;
+        if self == new {
+            return Diff::Same(self.clone());
+        }
+        match (self, new) {
+            (Record(old), Record(new)) if old.name == new.name => Diff::Record(old.diff(new)),
+            (Array(old), Array(new)) => Diff::Array(diff_lcs(old, new)),
+            (Map(old), Map(new)) => Diff::Map(diff_fields(old, new)),
+            (Seq(old), Seq(new)) if old.with_items(vec![]) == new.with_items(vec![]) => {
+                Diff::Seq(new.with_items(vec![]), diff_lcs(&old.items, &new.items))
+            }
+            _ => Diff::Changed(self.clone(), new.clone()),
+        }
+    }
+}
+
+/// Old keys in order, then the added ones.
+fn diff_fields<'a>(old: &FieldMap<'a>, new: &FieldMap<'a>) -> Vec<(Str<'a>, Diff<'a>)> {
+    let mut fields: Vec<_> = (old.iter())
+        .map(|(k, v)| match new.get(k) {
+            Some(nv) => (k.clone(), v.diff(nv)),
+            None => (k.clone(), Diff::Removed(v.clone())),
+        })
+        .collect();
+    fields.extend(
+        (new.iter())
+            .filter(|(k, _)| !old.contains_key(k))
+            .map(|(k, v)| (k.clone(), Diff::Added(v.clone()))),
+    );
+    fields
+}
+
+impl<'a> XmlNode<'a> {
+    fn diff(&self, new: &Self) -> RecordDiff<'a> {
+        let fields = diff_fields(&self.fields, &new.fields);
+        let len = self.children.len().max(new.children.len());
+        let children = (0..len)
+            .map(|i| match (self.children.get(i), new.children.get(i)) {
+                (Some(old), Some(new)) => old.diff(new),
+                (Some(old), None) => Diff::Removed(old.clone()),
+                (None, Some(new)) => Diff::Added(new.clone()),
+                (None, None) => unreachable!(),
+            })
+            .collect();
+        RecordDiff {
+            name: self.name.clone(),
+            fields,
+            children,
+        }
+    }
+}
+
+/// Aligns two sequences along their longest common subsequence.
+/// Each pair has the index in `old`, the index in `new`, or both.
+pub(crate) fn lcs_align<T>(
+    old: &[T],
+    new: &[T],
+    eq: impl Fn(&T, &T) -> bool,
+) -> Vec<(Option<usize>, Option<usize>)> {
+    // lcs[i][j] is the length of the LCS of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if eq(&old[i], &new[j]) {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut res = Vec::with_capacity(old.len().max(new.len()));
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && eq(&old[i], &new[j]) {
+            res.push((Some(i), Some(j)));
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            res.push((None, Some(j)));
+            j += 1;
+        } else {
+            res.push((Some(i), None));
+            i += 1;
+        }
+    }
+    res
+}
+
+fn diff_lcs<'a>(old: &[Pretty<'a>], new: &[Pretty<'a>]) -> Vec<Diff<'a>> {
+    (lcs_align(old, new, PartialEq::eq).into_iter())
+        .map(|pair| match pair {
+            (Some(i), Some(_)) => Diff::Same(old[i].clone()),
+            (Some(i), None) => Diff::Removed(old[i].clone()),
+            (None, Some(j)) => Diff::Added(new[j].clone()),
+            (None, None) => unreachable!(),
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy)]
+enum Marker {
+    Added,
+    Removed,
+    Changed,
+}
+
+impl Marker {
+    fn color<'a>(self, s: String, colored: bool) -> Str<'a> {
+        let color = match self {
+            Marker::Added => "32",
+            Marker::Removed => "31",
+            Marker::Changed => "33",
+        };
+        if colored {
+            format!("\x1b[{}m{}\x1b[0m", color, s).into()
+        } else {
+            s.into()
+        }
+    }
+
+    fn paint<'a>(self, s: &str, colored: bool) -> Str<'a> {
+        let sign = match self {
+            Marker::Added => '+',
+            Marker::Removed => '-',
+            Marker::Changed => '~',
+        };
+        self.color(format!("{} {}", sign, s), colored)
+    }
+
+    /// The whole subtree is added or removed, only the head is marked.
+    fn mark<'a>(self, pretty: &Pretty<'a>, colored: bool) -> Pretty<'a> {
+        match pretty {
+            Pretty::Record(xml) => Pretty::Record(XmlNode {
+                name: self.paint(&xml.name, colored),
+                ..xml.clone()
+            }),
+            _ => Pretty::Text(self.paint(&pretty.to_one_line_string(false), colored)),
+        }
+    }
+}
+
+fn is_leaf(pretty: &Pretty) -> bool {
+    !matches!(
+        pretty,
+        Pretty::Record(..) | Pretty::Array(..) | Pretty::Map(..) | Pretty::Seq(..)
+    )
+}
+
+fn arrow(old: &Pretty, new: &Pretty) -> String {
+    format!(
+        "{} → {}",
+        old.to_one_line_string(false),
+        new.to_one_line_string(false)
+    )
+}
+
+impl<'a> Diff<'a> {
+    pub fn is_same(&self) -> bool {
+        matches!(self, Diff::Same(..))
+    }
+
+    /// A tree that prints like the new one, with `+`/`-`/`~` markers
+    /// in front of the added, removed and changed nodes and fields.
+    /// Markers are green, red and yellow when `colored`.
+    pub fn to_pretty(&self, colored: bool) -> Pretty<'a> {
+        let mut list = self.to_pretties(colored);
+        if list.len() == 1 {
+            list.pop().unwrap()
+        } else {
+            Pretty::Array(list)
+        }
+    }
+
+    /// Changed records take two entries, the removed one and the added one.
+    fn to_pretties(&self, colored: bool) -> Vec<Pretty<'a>> {
+        use Marker::*;
+        match self {
+            Diff::Same(p) => vec![p.clone()],
+            Diff::Added(p) => vec![Added.mark(p, colored)],
+            Diff::Removed(p) => vec![Removed.mark(p, colored)],
+            Diff::Changed(old, new) if is_leaf(old) && is_leaf(new) => {
+                vec![Pretty::Text(Changed.paint(&arrow(old, new), colored))]
+            }
+            Diff::Changed(old, new) => vec![Removed.mark(old, colored), Added.mark(new, colored)],
+            Diff::Record(rd) => {
+                let fields = fields_to_pretty(&rd.fields, colored);
+                let children = (rd.children.iter())
+                    .flat_map(|d| d.to_pretties(colored))
+                    .collect();
+                let name = Changed.paint(&rd.name, colored);
+                vec![Pretty::Record(XmlNode::new(name, fields, children))]
+            }
+            Diff::Array(v) => {
+                let v = v.iter().flat_map(|d| d.to_pretties(colored)).collect();
+                vec![Pretty::Array(v)]
+            }
+            Diff::Seq(s, v) => {
+                let v = v.iter().flat_map(|d| d.to_pretties(colored)).collect();
+                vec![Pretty::Seq(s.with_items(v))]
+            }
+            Diff::Map(m) => vec![Pretty::Map(fields_to_pretty(m, colored))],
+        }
+    }
+}
+
+/// Only the keys are marked.
+fn fields_to_pretty<'a>(fields: &[(Str<'a>, Diff<'a>)], colored: bool) -> FieldMap<'a> {
+    use Marker::*;
+    (fields.iter())
+        .map(|(k, d)| match d {
+            Diff::Same(p) => (k.clone(), p.clone()),
+            Diff::Added(p) => (Added.paint(k, colored), p.clone()),
+            Diff::Removed(p) => (Removed.paint(k, colored), p.clone()),
+            // The key is marked already.
+            Diff::Changed(old, new) if is_leaf(old) && is_leaf(new) => {
+                let value = Changed.color(arrow(old, new), colored);
+                (Changed.paint(k, colored), Pretty::Text(value))
+            }
+            _ => (Changed.paint(k, colored), d.to_pretty(colored)),
+        })
+        .collect()
+}
+
+impl PrettyConfig {
+    /// Renders the difference between `old` and `new` in the unicode style.
+    pub fn unicode_diff(&mut self, out: &mut String, old: &Pretty, new: &Pretty) -> usize {
+        let diff = old.diff(new).to_pretty(self.colors);
+        self.unicode(out, &diff)
+    }
+}
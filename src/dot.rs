@@ -0,0 +1,120 @@
+//! Graphviz DOT export. Records are nodes and children are edges,
+//! and a [`Pretty::Ref`] is an edge to the record it refers to,
+//! so a shared subtree is drawn once with several incoming edges.
+//!
+//! ```rust
+//! use pretty_xmlish::{Pretty, PrettyConfig};
+//! let scan = Pretty::record("Scan").field("table", "t1").id(1).build();
+//! let join = Pretty::record("Join").child(scan).child(Pretty::Ref(1)).build();
+//! let mut out = String::new();
+//! PrettyConfig::default().dot(&mut out, &join);
+//! assert_eq!(out, r#"digraph {
+//!   node [shape=box];
+//!   n0 [label="Join"];
+//!   r1 [label="Scan [#1]\ntable: t1"];
+//!   n0 -> r1;
+//!   n0 -> r1;
+//! }
+//! "#);
+//! ```
+//!
+//! Fields and notes are part of the label of the node, and the notes
+//! are its tooltip too, which becomes the title of the node in SVG.
+//! Anything other than a record is a node labelled with its one-line form.
+
+use crate::{note::NOTE_MARK, Pretty, PrettyConfig, Str, XmlNode};
+
+struct DotWriter<'o> {
+    out: &'o mut String,
+    /// For the names of the nodes without an id.
+    next: usize,
+}
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+impl DotWriter<'_> {
+    fn node_line(&mut self, name: &str, lines: impl Iterator<Item = String>, notes: &[Str]) {
+        let label = lines.map(|l| escape(&l)).collect::<Vec<_>>().join("\\n");
+        self.out
+            .push_str(&format!("  {} [label=\"{}\"", name, label));
+        // The title of the node in SVG
+        if !notes.is_empty() {
+            let tooltip = notes.iter().map(|n| escape(n)).collect::<Vec<_>>();
+            self.out
+                .push_str(&format!(", tooltip=\"{}\"", tooltip.join("\\n")));
+        }
+        self.out.push_str("];\n");
+    }
+
+    fn fresh(&mut self) -> String {
+        let name = format!("n{}", self.next);
+        self.next += 1;
+        name
+    }
+
+    /// The name of the node of `pretty`, after writing it.
+    fn node(&mut self, pretty: &Pretty) -> String {
+        match pretty {
+            Pretty::Record(xml) => self.record(xml),
+            Pretty::Ref(id) => format!("r{}", id),
+            Pretty::Linearized(p, _) => self.node(p),
+            p => {
+                let name = self.fresh();
+                self.node_line(&name, std::iter::once(p.to_one_line_string(false)), &[]);
+                name
+            }
+        }
+    }
+
+    fn record(&mut self, xml: &XmlNode) -> String {
+        let name = match xml.id {
+            Some(id) => format!("r{}", id),
+            None => self.fresh(),
+        };
+        let fields = (xml.fields.iter()).map(|(k, v)| match v {
+            Pretty::Elided(..) => v.to_one_line_string(false),
+            _ => format!("{}: {}", k, v.to_one_line_string(false)),
+        });
+        let notes = (xml.notes.iter()).map(|n| format!("{}{}", NOTE_MARK, n));
+        let header = std::iter::once(xml.header().into_owned());
+        self.node_line(&name, header.chain(fields).chain(notes), &xml.notes);
+        for child in &xml.children {
+            let child = self.node(child);
+            self.out.push_str(&format!("  {} -> {};\n", name, child));
+        }
+        name
+    }
+}
+
+impl PrettyConfig {
+    /// Writes a Graphviz digraph, see [`crate::dot`].
+    pub fn dot(&self, out: &mut String, pretty: &Pretty) {
+        let pretty = self.preprocess_plain(pretty);
+        out.push_str("digraph {\n  node [shape=box];\n");
+        let mut writer = DotWriter {
+            out: &mut *out,
+            next: 0,
+        };
+        match &*pretty {
+            // The elements of an array at the top are separate trees
+            Pretty::Array(v) => v.iter().for_each(|p| {
+                writer.node(p);
+            }),
+            p => {
+                writer.node(p);
+            }
+        }
+        out.push_str("}\n");
+    }
+}
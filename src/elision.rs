@@ -0,0 +1,98 @@
+use crate::{Pretty, Str, XmlNode};
+
+/// Limits on how many array elements, fields and children are printed.
+/// `Some(n)` keeps the first `n` and the last `n` entries and replaces
+/// the middle with `… 480 more`.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Elision {
+    pub max_elements: Option<usize>,
+    pub max_fields: Option<usize>,
+    pub max_children: Option<usize>,
+}
+
+pub(crate) fn ellipsis<'a>(hidden: usize) -> Str<'a> {
+    format!("… {} more", hidden).into()
+}
+
+/// Keeps the head and the tail, the middle is summarized by `marker`.
+/// Eliding a single entry saves nothing, so that is not done.
+fn elide_vec<T: Clone>(v: &[T], keep: Option<usize>, marker: impl FnOnce(usize) -> T) -> Vec<T> {
+    match keep {
+        Some(keep) if v.len() > keep * 2 + 1 => {
+            let hidden = v.len() - keep * 2;
+            let mut res = Vec::with_capacity(keep * 2 + 1);
+            res.extend_from_slice(&v[..keep]);
+            res.push(marker(hidden));
+            res.extend_from_slice(&v[v.len() - keep..]);
+            res
+        }
+        _ => v.to_vec(),
+    }
+}
+
+/// Only the value of the elided fields is printed, the key is ignored.
+fn elide_map<'a>(
+    fields: &[(Str<'a>, Pretty<'a>)],
+    keep: Option<usize>,
+) -> Vec<(Str<'a>, Pretty<'a>)> {
+    elide_vec(fields, keep, |n| (Str::Borrowed(""), Pretty::Elided(n)))
+}
+
+impl Elision {
+    pub fn is_noop(&self) -> bool {
+        self.max_elements.is_none() && self.max_fields.is_none() && self.max_children.is_none()
+    }
+}
+
+impl<'a> XmlNode<'a> {
+    pub fn elide(&self, elision: &Elision) -> Self {
+        let fields = (self.fields.iter())
+            .map(|(k, v)| (k.clone(), v.elide(elision)))
+            .collect::<Vec<_>>();
+        let fields = elide_map(&fields, elision.max_fields);
+        let children = (self.children.iter())
+            .map(|c| c.elide(elision))
+            .collect::<Vec<_>>();
+        let children = elide_vec(&children, elision.max_children, Pretty::Elided);
+        self.rebuild(fields.into(), children)
+    }
+}
+
+impl<'a> Pretty<'a> {
+    /// Also once laid out on one line by a renderer.
+    pub fn is_elided(&self) -> bool {
+        match self {
+            Pretty::Elided(..) => true,
+            Pretty::Linearized(p, _) => p.is_elided(),
+            _ => false,
+        }
+    }
+
+    /// Applies the limits of `elision` recursively.
+    /// The result is what all the renderers are going to print.
+    pub fn elide(&self, elision: &Elision) -> Self {
+        use Pretty::*;
+        match self {
+            Record(xml) => Record(xml.elide(elision)),
+            Array(v) => {
+                let v = v.iter().map(|p| p.elide(elision)).collect::<Vec<_>>();
+                Array(elide_vec(&v, elision.max_elements, Elided))
+            }
+            Seq(s) => {
+                let items = s.items.iter().map(|p| p.elide(elision)).collect::<Vec<_>>();
+                let items = elide_vec(&items, elision.max_elements, Elided);
+                Seq(s.with_items(items))
+            }
+            Map(m) => {
+                let m = m.iter().map(|(k, v)| (k.clone(), v.elide(elision)));
+                Map(elide_map(&m.collect::<Vec<_>>(), elision.max_fields).into())
+            }
+            Table(t) => {
+                let t = t.map_cells(|p| p.elide(elision));
+                let rows = elide_vec(&t.rows, elision.max_elements, |n| vec![Elided(n)]);
+                Table(crate::Table { rows, ..t })
+            }
+            Text(..) | Scalar(..) | Elided(..) | Linearized(..) | Ref(..) => self.clone(),
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! Scalar expressions, printed infix on one line and broken
+//! at the top-level operator when they do not fit.
+//!
+//! ```rust
+//! use pretty_xmlish::{Expr, Pretty};
+//! let sum = Expr::binary("+", Expr::lit("$3"), Expr::lit("$4"));
+//! let pred = Expr::binary("=", Expr::lit("$0"), sum);
+//! let filter = Pretty::record("Filter")
+//!     .field("predicate", pred)
+//!     .field("limit", Expr::call("least", vec![Expr::lit(Pretty::int(10)), Expr::lit("$1")]))
+//!     .build();
+//! assert_eq!(
+//!     filter.to_one_line_string(false),
+//!     "Filter { predicate: ($0 = ($3 + $4)), limit: least(10, $1) }",
+//! );
+//! ```
+//!
+//! Expressions are turned into [`Seq`]s, so once broken, every operand
+//! is on its own line and the operator ends the line before it:
+//!
+//! ```rust
+//! use pretty_xmlish::{Expr, Pretty, PrettyConfig};
+//! let sum = Expr::binary("+", Expr::lit("$3"), Expr::lit("$4"));
+//! let pred = Expr::binary("=", Expr::lit("$0"), sum);
+//! let filter = Pretty::record("Filter").field("predicate", pred).build();
+//! let mut config = PrettyConfig {
+//!     need_boundaries: false,
+//!     width: 20,
+//!     ..Default::default()
+//! };
+//! let mut out = String::new();
+//! config.clone().unicode(&mut out, &filter);
+//! assert_eq!(out, "\
+//! Filter
+//! └── predicate: (
+//!     ┌── $0 =
+//!     └── ($3 + $4)
+//!     )");
+//! let mut out = String::new();
+//! config.ascii(&mut out, &filter);
+//! assert_eq!(out, "\
+//! Filter {
+//!     predicate: (
+//!         $0 =
+//!         ($3 + $4)
+//!     )
+//! }");
+//! ```
+
+use crate::{Pretty, Seq, Str};
+
+/// Only sugar to build [`Seq`]s: there is no `Pretty::Expr`, so once turned
+/// into a [`Pretty`], an expression is a nest of seqs that neither the
+/// renderers nor the parsers can turn back into an `Expr`.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Expr<'a> {
+    /// A column, a constant, or anything printed as is.
+    Literal(Pretty<'a>),
+    /// `-x`, or `NOT x` when the operator is a word.
+    Unary { op: Str<'a>, operand: Box<Self> },
+    /// `(lhs op rhs)`, always parenthesized.
+    Binary {
+        op: Str<'a>,
+        lhs: Box<Self>,
+        rhs: Box<Self>,
+    },
+    /// `func(arg, ..)`
+    Call { func: Str<'a>, args: Vec<Self> },
+}
+
+impl<'a> Expr<'a> {
+    pub fn lit(value: impl Into<Pretty<'a>>) -> Self {
+        Self::Literal(value.into())
+    }
+
+    pub fn unary(op: impl Into<Str<'a>>, operand: Self) -> Self {
+        Self::Unary {
+            op: op.into(),
+            operand: Box::new(operand),
+        }
+    }
+
+    pub fn binary(op: impl Into<Str<'a>>, lhs: Self, rhs: Self) -> Self {
+        Self::Binary {
+            op: op.into(),
+            lhs: Box::new(lhs),
+            rhs: Box::new(rhs),
+        }
+    }
+
+    pub fn call(func: impl Into<Str<'a>>, args: Vec<Self>) -> Self {
+        Self::Call {
+            func: func.into(),
+            args,
+        }
+    }
+
+    /// The nest of [`Seq`]s this expression is printed as, see [`Expr`].
+    pub fn to_pretty(&self) -> Pretty<'a> {
+        match self {
+            Expr::Literal(p) => p.clone(),
+            Expr::Unary { op, operand } => {
+                let is_word = op.ends_with(|c: char| c.is_alphanumeric());
+                let op = if is_word {
+                    format!("{} ", op)
+                } else {
+                    op.to_string()
+                };
+                Seq::new(op, "", "", vec![operand.to_pretty()]).into()
+            }
+            Expr::Binary { op, lhs, rhs } => {
+                let items = vec![lhs.to_pretty(), rhs.to_pretty()];
+                Seq::new("(", format!(" {} ", op), ")", items).into()
+            }
+            Expr::Call { func, args } => {
+                let args = args.iter().map(Self::to_pretty).collect();
+                Seq::new(format!("{}(", func), ", ", ")", args).into()
+            }
+        }
+    }
+}
+
+impl<'a> From<Expr<'a>> for Pretty<'a> {
+    fn from(expr: Expr<'a>) -> Self {
+        expr.to_pretty()
+    }
+}
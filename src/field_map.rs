@@ -0,0 +1,359 @@
+//! The fields of a record, in the order they were added.
+//!
+//! ```rust
+//! use pretty_xmlish::{field_map::OnDuplicate, FieldMap, Pretty};
+//! let mut fields = FieldMap::from([("table", "t1".into()), ("limit", "10".into())]);
+//! assert_eq!(fields.get("limit"), Some(&"10".into()));
+//! fields.insert("table".into(), "t2".into());
+//! *fields.entry("offset".into()).or_insert("0".into()) = "5".into();
+//! let keys: Vec<_> = fields.keys().collect();
+//! assert_eq!(keys, ["table", "limit", "offset"]);
+//!
+//! let twice = vec![("a", Pretty::from("1")), ("a", "2".into())];
+//! assert!(FieldMap::from_entries(twice.clone(), OnDuplicate::Error).is_err());
+//! let first = FieldMap::from_entries(twice, OnDuplicate::KeepFirst).unwrap();
+//! assert_eq!(first["a"], "1".into());
+//! ```
+
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::Index;
+
+use crate::{Pretty, Str};
+
+/// An insertion-ordered map from keys to values. Lookups are
+/// `O(log n)`, insertions and removals `O(n)`, which is fine for the
+/// handful of fields a record has.
+///
+/// Conversions from lists keep the position of the first occurrence of a
+/// key and the value of the last one, like [`FieldMap::insert`] does.
+/// See [`FieldMap::from_entries`] for the other policies.
+///
+/// Elided fields, whose value is [`Pretty::Elided`], are kept in order
+/// but cannot be looked up, so their key never clashes with a field.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct FieldMap<'a> {
+    entries: Vec<(Str<'a>, Pretty<'a>)>,
+    /// Positions in `entries`, sorted by key.
+    index: Vec<usize>,
+}
+
+/// What [`FieldMap::from_entries`] does with a key that is already there.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OnDuplicate {
+    /// The last value wins, at the position of the first one.
+    Replace,
+    /// The first value wins.
+    KeepFirst,
+    Error,
+}
+
+/// A key that appears twice with [`OnDuplicate::Error`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DuplicateKey(pub String);
+
+impl Display for DuplicateKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "duplicate key `{}`", self.0)
+    }
+}
+
+impl std::error::Error for DuplicateKey {}
+
+impl<'a> FieldMap<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+            index: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn from_entries<K: Into<Str<'a>>>(
+        entries: impl IntoIterator<Item = (K, Pretty<'a>)>,
+        policy: OnDuplicate,
+    ) -> Result<Self, DuplicateKey> {
+        let mut map = Self::new();
+        for (k, v) in entries {
+            if v.is_elided() {
+                map.entries.push((k.into(), v));
+                continue;
+            }
+            match map.entry(k.into()) {
+                Entry::Vacant(e) => {
+                    e.insert(v);
+                }
+                Entry::Occupied(mut e) => match policy {
+                    OnDuplicate::Replace => {
+                        e.insert(v);
+                    }
+                    OnDuplicate::KeepFirst => {}
+                    OnDuplicate::Error => return Err(DuplicateKey(e.key().to_string())),
+                },
+            }
+        }
+        Ok(map)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The fields in order.
+    pub fn as_slice(&self) -> &[(Str<'a>, Pretty<'a>)] {
+        &self.entries
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, (Str<'a>, Pretty<'a>)> {
+        self.entries.iter()
+    }
+
+    /// Keys cannot be changed in place, since they are indexed.
+    pub fn iter_mut(&mut self) -> impl DoubleEndedIterator<Item = (&Str<'a>, &mut Pretty<'a>)> {
+        self.entries.iter_mut().map(|(k, v)| (&*k, v))
+    }
+
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &str> {
+        self.entries.iter().map(|(k, _)| k.as_ref())
+    }
+
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &Pretty<'a>> {
+        self.entries.iter().map(|(_, v)| v)
+    }
+
+    /// Same keys in the same order, `f` applied to every value.
+    /// The fields that `f` elides cannot be looked up anymore.
+    pub fn map_values<'b>(&'b self, mut f: impl FnMut(&'b Pretty<'a>) -> Pretty<'a>) -> Self {
+        let mut map = Self::with_capacity(self.len());
+        map.extend(self.entries.iter().map(|(k, v)| (k.clone(), f(v))));
+        map
+    }
+
+    /// `Ok` with the position in `index` of `key`, or `Err` with where it would go.
+    fn search(&self, key: &str) -> Result<usize, usize> {
+        (self.index).binary_search_by(|&i| self.entries[i].0.as_ref().cmp(key))
+    }
+
+    /// The position of `key` among the fields.
+    pub fn position(&self, key: &str) -> Option<usize> {
+        self.search(key).ok().map(|slot| self.index[slot])
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.search(key).is_ok()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Pretty<'a>> {
+        self.position(key).map(|i| &self.entries[i].1)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Pretty<'a>> {
+        self.position(key).map(|i| &mut self.entries[i].1)
+    }
+
+    /// A new key goes last, an existing key keeps its position
+    /// and its previous value is returned.
+    /// An elided field always goes last, without replacing anything.
+    pub fn insert(&mut self, key: Str<'a>, value: Pretty<'a>) -> Option<Pretty<'a>> {
+        if value.is_elided() {
+            self.entries.push((key, value));
+            return None;
+        }
+        match self.entry(key) {
+            Entry::Occupied(mut e) => Some(e.insert(value)),
+            Entry::Vacant(e) => {
+                e.insert(value);
+                None
+            }
+        }
+    }
+
+    /// The fields after it move up.
+    pub fn remove(&mut self, key: &str) -> Option<Pretty<'a>> {
+        match self.search(key) {
+            Ok(slot) => Some(self.remove_at(slot)),
+            Err(_) => None,
+        }
+    }
+
+    fn remove_at(&mut self, slot: usize) -> Pretty<'a> {
+        let pos = self.index.remove(slot);
+        for i in &mut self.index {
+            if *i > pos {
+                *i -= 1;
+            }
+        }
+        self.entries.remove(pos).1
+    }
+
+    pub fn entry(&mut self, key: Str<'a>) -> Entry<'_, 'a> {
+        match self.search(&key) {
+            Ok(slot) => Entry::Occupied(OccupiedEntry { map: self, slot }),
+            Err(slot) => Entry::Vacant(VacantEntry {
+                map: self,
+                key,
+                slot,
+            }),
+        }
+    }
+}
+
+pub enum Entry<'m, 'a> {
+    Occupied(OccupiedEntry<'m, 'a>),
+    Vacant(VacantEntry<'m, 'a>),
+}
+
+pub struct OccupiedEntry<'m, 'a> {
+    map: &'m mut FieldMap<'a>,
+    /// Position in the index.
+    slot: usize,
+}
+
+pub struct VacantEntry<'m, 'a> {
+    map: &'m mut FieldMap<'a>,
+    key: Str<'a>,
+    /// Where the key goes in the index.
+    slot: usize,
+}
+
+impl<'m, 'a> Entry<'m, 'a> {
+    pub fn key(&self) -> &str {
+        match self {
+            Entry::Occupied(e) => e.key(),
+            Entry::Vacant(e) => &e.key,
+        }
+    }
+
+    pub fn or_insert(self, value: Pretty<'a>) -> &'m mut Pretty<'a> {
+        self.or_insert_with(|| value)
+    }
+
+    pub fn or_insert_with(self, value: impl FnOnce() -> Pretty<'a>) -> &'m mut Pretty<'a> {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(value()),
+        }
+    }
+
+    pub fn and_modify(mut self, f: impl FnOnce(&mut Pretty<'a>)) -> Self {
+        if let Entry::Occupied(e) = &mut self {
+            f(e.get_mut());
+        }
+        self
+    }
+}
+
+impl<'m, 'a> OccupiedEntry<'m, 'a> {
+    fn pos(&self) -> usize {
+        self.map.index[self.slot]
+    }
+
+    pub fn key(&self) -> &str {
+        &self.map.entries[self.pos()].0
+    }
+
+    pub fn get(&self) -> &Pretty<'a> {
+        &self.map.entries[self.pos()].1
+    }
+
+    pub fn get_mut(&mut self) -> &mut Pretty<'a> {
+        let pos = self.pos();
+        &mut self.map.entries[pos].1
+    }
+
+    pub fn into_mut(self) -> &'m mut Pretty<'a> {
+        let pos = self.pos();
+        &mut self.map.entries[pos].1
+    }
+
+    /// Returns the previous value.
+    pub fn insert(&mut self, value: Pretty<'a>) -> Pretty<'a> {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    pub fn remove(self) -> Pretty<'a> {
+        self.map.remove_at(self.slot)
+    }
+}
+
+impl<'m, 'a> VacantEntry<'m, 'a> {
+    /// The new field goes last, and can be looked up unless it is elided.
+    pub fn insert(self, value: Pretty<'a>) -> &'m mut Pretty<'a> {
+        let pos = self.map.entries.len();
+        if !value.is_elided() {
+            self.map.index.insert(self.slot, pos);
+        }
+        self.map.entries.push((self.key, value));
+        &mut self.map.entries[pos].1
+    }
+}
+
+impl Debug for FieldMap<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(&self.entries).finish()
+    }
+}
+
+impl<'a> Index<&str> for FieldMap<'a> {
+    type Output = Pretty<'a>;
+
+    fn index(&self, key: &str) -> &Pretty<'a> {
+        self.get(key)
+            .unwrap_or_else(|| panic!("no field `{}`", key))
+    }
+}
+
+impl<'a, K: Into<Str<'a>>> Extend<(K, Pretty<'a>)> for FieldMap<'a> {
+    fn extend<I: IntoIterator<Item = (K, Pretty<'a>)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k.into(), v);
+        }
+    }
+}
+
+impl<'a, K: Into<Str<'a>>> FromIterator<(K, Pretty<'a>)> for FieldMap<'a> {
+    fn from_iter<I: IntoIterator<Item = (K, Pretty<'a>)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+/// A key given twice is not an error: the last value wins, at the position
+/// of the first one. [`FieldMap::from_entries`] can reject it instead.
+impl<'a, K: Into<Str<'a>>> From<Vec<(K, Pretty<'a>)>> for FieldMap<'a> {
+    fn from(v: Vec<(K, Pretty<'a>)>) -> Self {
+        v.into_iter().collect()
+    }
+}
+
+/// Like the conversion from a [`Vec`].
+impl<'a, K: Into<Str<'a>>, const N: usize> From<[(K, Pretty<'a>); N]> for FieldMap<'a> {
+    fn from(v: [(K, Pretty<'a>); N]) -> Self {
+        v.into_iter().collect()
+    }
+}
+
+impl<'a> IntoIterator for FieldMap<'a> {
+    type Item = (Str<'a>, Pretty<'a>);
+    type IntoIter = std::vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'m, 'a> IntoIterator for &'m FieldMap<'a> {
+    type Item = &'m (Str<'a>, Pretty<'a>);
+    type IntoIter = std::slice::Iter<'m, (Str<'a>, Pretty<'a>)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter()
+    }
+}
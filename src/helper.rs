@@ -7,6 +7,6 @@ pub fn delegate_fmt<'a>(me: &Pretty<'a>, f: &mut Formatter<'_>, mut buffer: Stri
         need_boundaries: false,
         ..PrettyConfig::default()
     };
-    config.unicode(&mut buffer, &me);
+    config.unicode(&mut buffer, me);
     f.write_str(&buffer)
 }
@@ -0,0 +1,110 @@
+//! An HTML fragment of nested lists: a record is an item with its fields
+//! and children in a list below it, and its notes are the tooltip of its
+//! name.
+//!
+//! ```rust
+//! use pretty_xmlish::{Pretty, PrettyConfig};
+//! let scan = Pretty::record("Scan").field("table", "t1").note("a < b").build();
+//! let mut out = String::new();
+//! PrettyConfig::default().html(&mut out, &scan);
+//! assert_eq!(out, r#"<ul class="pretty-xmlish">
+//!   <li><span title="a &lt; b">Scan</span>
+//!     <ul>
+//!       <li>table: t1</li>
+//!     </ul>
+//!   </li>
+//! </ul>
+//! "#);
+//! ```
+//!
+//! Anything other than a record is an item with its one-line form.
+
+use crate::{Pretty, PrettyConfig, XmlNode};
+
+fn escape(s: &str, out: &mut String) {
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\n' => out.push_str("&#10;"),
+            c => out.push(c),
+        }
+    }
+}
+
+struct HtmlWriter<'o> {
+    out: &'o mut String,
+}
+
+impl HtmlWriter<'_> {
+    fn begin_line(&mut self, depth: usize) {
+        self.out.extend(std::iter::repeat_n(' ', depth * 2));
+    }
+
+    fn leaf(&mut self, text: &str, depth: usize) {
+        self.begin_line(depth);
+        self.out.push_str("<li>");
+        escape(text, self.out);
+        self.out.push_str("</li>\n");
+    }
+
+    fn node(&mut self, pretty: &Pretty, depth: usize) {
+        match pretty {
+            Pretty::Record(xml) => self.record(xml, depth),
+            Pretty::Linearized(p, _) => self.node(p, depth),
+            p => self.leaf(&p.to_one_line_string(false), depth),
+        }
+    }
+
+    fn record(&mut self, xml: &XmlNode, depth: usize) {
+        self.begin_line(depth);
+        self.out.push_str("<li><span");
+        if !xml.notes.is_empty() {
+            self.out.push_str(" title=\"");
+            escape(&xml.notes.join("\n"), self.out);
+            self.out.push('"');
+        }
+        self.out.push('>');
+        escape(&xml.header(), self.out);
+        self.out.push_str("</span>");
+        if xml.fields.is_empty() && xml.children.is_empty() {
+            self.out.push_str("</li>\n");
+            return;
+        }
+        self.out.push('\n');
+        self.begin_line(depth + 1);
+        self.out.push_str("<ul>\n");
+        for (k, v) in xml.fields.iter() {
+            let text = v.to_one_line_string(false);
+            if v.is_elided() {
+                self.leaf(&text, depth + 2);
+            } else {
+                self.leaf(&format!("{}: {}", k, text), depth + 2);
+            }
+        }
+        for child in &xml.children {
+            self.node(child, depth + 2);
+        }
+        self.begin_line(depth + 1);
+        self.out.push_str("</ul>\n");
+        self.begin_line(depth);
+        self.out.push_str("</li>\n");
+    }
+}
+
+impl PrettyConfig {
+    /// Writes an HTML list, see [`crate::html`].
+    pub fn html(&self, out: &mut String, pretty: &Pretty) {
+        let pretty = self.preprocess_plain(pretty);
+        out.push_str("<ul class=\"pretty-xmlish\">\n");
+        let mut writer = HtmlWriter { out: &mut *out };
+        match &*pretty {
+            // The elements of an array at the top are separate trees
+            Pretty::Array(v) => v.iter().for_each(|p| writer.node(p, 1)),
+            p => writer.node(p, 1),
+        }
+        out.push_str("</ul>\n");
+    }
+}
@@ -0,0 +1,263 @@
+//! JSON documents. An object is a record: its `"name"` string is the name
+//! of the record, its `"children"` array holds the children and the other
+//! members are the fields. An object with neither is a [`Pretty::Map`].
+//! Arrays are arrays, and every other value is a
+//! text, except numbers, booleans and `null`, which are [`Scalar`]s.
+//!
+//! ```rust
+//! use pretty_xmlish::Pretty;
+//! let json = r#"{
+//!   "name": "BatchExchange", "dist": "Single", "order": [],
+//!   "children": [{ "name": "BatchScan", "table": "t1", "limit": 10 }]
+//! }"#;
+//! assert_eq!(Pretty::parse_json(json).unwrap(), Pretty::simple_record("BatchExchange",
+//!     vec![("dist", "Single".into()), ("order", Pretty::Array(vec![]))],
+//!     vec![Pretty::childless_record("BatchScan", vec![("table", "t1".into()), ("limit", Pretty::int(10))])],
+//! ));
+//! ```
+
+use std::borrow::Cow;
+
+use crate::{
+    parse::{ParseError, ParseResult},
+    Pretty, Scalar, Str, XmlNode,
+};
+
+/// `-?(0|[1-9][0-9]*)(.[0-9]+)?([eE][+-]?[0-9]+)?`, which Rust would
+/// parse along with `NaN`, `inf`, `+1` or `.5`.
+fn is_number(s: &str) -> bool {
+    fn digits(s: &str) -> Option<&str> {
+        let rest = s.trim_start_matches(|c: char| c.is_ascii_digit());
+        (rest.len() < s.len()).then_some(rest)
+    }
+    let s = s.strip_prefix('-').unwrap_or(s);
+    let Some(rest) = s.strip_prefix('0').or_else(|| digits(s)) else {
+        return false;
+    };
+    let rest = match rest.strip_prefix('.') {
+        Some(fraction) => digits(fraction),
+        None => Some(rest),
+    };
+    let rest = match rest.and_then(|r| r.strip_prefix(['e', 'E'])) {
+        Some(exponent) => digits(exponent.strip_prefix(['+', '-']).unwrap_or(exponent)),
+        None => rest,
+    };
+    rest == Some("")
+}
+
+/// The code unit of the four hex digits after `\u`.
+fn hex4(chars: &mut std::str::CharIndices) -> Option<u32> {
+    let hex: String = chars.take(4).map(|(_, c)| c).collect();
+    let is_hex = hex.len() == 4 && hex.chars().all(|c| c.is_ascii_hexdigit());
+    is_hex.then(|| u32::from_str_radix(&hex, 16).unwrap())
+}
+
+struct JsonReader<'s> {
+    src: &'s str,
+    pos: usize,
+}
+
+enum Member<'s> {
+    Name(Str<'s>),
+    Children(Vec<Pretty<'s>>),
+    Field(Str<'s>, Pretty<'s>),
+}
+
+impl<'s> JsonReader<'s> {
+    fn rest(&self) -> &'s str {
+        &self.src[self.pos..]
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> ParseResult<T> {
+        Err(ParseError {
+            pos: self.pos,
+            message: message.into(),
+        })
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        let ok = self.rest().starts_with(s);
+        if ok {
+            self.pos += s.len();
+        }
+        ok
+    }
+
+    fn expect(&mut self, s: &str) -> ParseResult<()> {
+        if self.eat(s) {
+            Ok(())
+        } else {
+            self.error(format!("expected `{}`", s))
+        }
+    }
+
+    fn skip_ws(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// Comma-separated items until `end`, the opening bracket is consumed.
+    fn items(
+        &mut self,
+        end: &str,
+        mut item: impl FnMut(&mut Self) -> ParseResult<()>,
+    ) -> ParseResult<()> {
+        self.skip_ws();
+        if self.eat(end) {
+            return Ok(());
+        }
+        loop {
+            self.skip_ws();
+            item(self)?;
+            self.skip_ws();
+            if self.eat(end) {
+                return Ok(());
+            }
+            self.expect(",")?;
+        }
+    }
+
+    fn value(&mut self) -> ParseResult<Pretty<'s>> {
+        if self.eat("{") {
+            return self.object();
+        }
+        if self.eat("[") {
+            let mut v = vec![];
+            self.items("]", |r| {
+                v.push(r.value()?);
+                Ok(())
+            })?;
+            return Ok(Pretty::Array(v));
+        }
+        if self.rest().starts_with('"') {
+            return Ok(Pretty::Text(self.string()?));
+        }
+        let rest = self.rest();
+        let len = (rest.find(|c: char| !(c.is_ascii_alphanumeric() || "+-.".contains(c))))
+            .unwrap_or(rest.len());
+        let literal = &rest[..len];
+        let scalar = match literal {
+            "true" => Scalar::Bool(true),
+            "false" => Scalar::Bool(false),
+            "null" => Scalar::Null,
+            _ if !is_number(literal) => return self.error("expected a value"),
+            _ => match (literal.parse(), literal.parse::<f64>()) {
+                (Ok(i), _) => Scalar::Int(i),
+                (_, Ok(x)) if x.is_finite() => Scalar::Float(x),
+                _ => return self.error("number out of range"),
+            },
+        };
+        self.pos += len;
+        Ok(Pretty::Scalar(scalar))
+    }
+
+    fn object(&mut self) -> ParseResult<Pretty<'s>> {
+        let mut name = None;
+        let mut fields = vec![];
+        let mut children = None;
+        self.items("}", |r| {
+            match r.member()? {
+                Member::Name(n) => name = Some(n),
+                Member::Children(c) => children = Some(c),
+                Member::Field(k, v) => fields.push((k, v)),
+            }
+            Ok(())
+        })?;
+        if name.is_none() && children.is_none() {
+            return Ok(Pretty::Map(fields.into()));
+        }
+        let name = name.unwrap_or_default();
+        let children = children.unwrap_or_default();
+        Ok(Pretty::Record(XmlNode::new(name, fields.into(), children)))
+    }
+
+    fn member(&mut self) -> ParseResult<Member<'s>> {
+        let key = self.string()?;
+        self.skip_ws();
+        self.expect(":")?;
+        self.skip_ws();
+        let is_string = self.rest().starts_with('"');
+        let value = self.value()?;
+        Ok(match (key.as_ref(), value) {
+            ("name", Pretty::Text(name)) if is_string => Member::Name(name),
+            ("children", Pretty::Array(children)) => Member::Children(children),
+            (_, value) => Member::Field(key, value),
+        })
+    }
+
+    /// Borrowed from the input unless there are escapes.
+    fn string(&mut self) -> ParseResult<Str<'s>> {
+        self.expect("\"")?;
+        let rest = self.rest();
+        let end = rest.find(['"', '\\']);
+        if let Some(i) = end.filter(|&i| rest[i..].starts_with('"')) {
+            self.pos += i + 1;
+            return Ok(Cow::Borrowed(&rest[..i]));
+        }
+        let mut s = String::new();
+        let mut chars = rest.char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos += i + 1;
+                    return Ok(s.into());
+                }
+                '\\' => {
+                    let escaped = match chars.next().map(|(_, c)| c) {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('u') => {
+                            let Some(code) = hex4(&mut chars) else {
+                                self.pos += i;
+                                return self.error("invalid unicode escape");
+                            };
+                            // A high surrogate followed by a low one is a pair
+                            let mut ahead = chars.clone();
+                            let low = (ahead.next().map(|(_, c)| c) == Some('\\')
+                                && ahead.next().map(|(_, c)| c) == Some('u'))
+                            .then(|| hex4(&mut ahead))
+                            .flatten()
+                            .filter(|low| (0xDC00..0xE000).contains(low));
+                            match low {
+                                Some(low) if (0xD800..0xDC00).contains(&code) => {
+                                    chars = ahead;
+                                    let code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                                    char::from_u32(code).unwrap()
+                                }
+                                // Lone surrogates
+                                _ => char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER),
+                            }
+                        }
+                        Some(c @ ('"' | '\\' | '/')) => c,
+                        _ => {
+                            self.pos += i;
+                            return self.error("invalid escape");
+                        }
+                    };
+                    s.push(escaped);
+                }
+                c => s.push(c),
+            }
+        }
+        self.pos = self.src.len();
+        self.error("unclosed string")
+    }
+}
+
+impl<'s> Pretty<'s> {
+    /// Reads a JSON document, see the [module docs](crate::json).
+    pub fn parse_json(src: &'s str) -> ParseResult<Self> {
+        let mut reader = JsonReader { src, pos: 0 };
+        reader.eat("\u{feff}");
+        reader.skip_ws();
+        let pretty = reader.value()?;
+        reader.skip_ws();
+        if !reader.rest().is_empty() {
+            return reader.error("unexpected trailing input");
+        }
+        Ok(pretty)
+    }
+}
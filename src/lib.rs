@@ -2,12 +2,11 @@
 //!
 //! ```rust
 //! use pretty_xmlish::{Pretty, PrettyConfig};
-//! use std::collections::BTreeMap;
 //! // This class controls the expected width, indent size, and more.
 //! let mut config = PrettyConfig::default();
 //! // Other factory methods are available
 //! let pretty = Pretty::simple_record("BatchNestedLoopJoin",
-//!     BTreeMap::new(), // fields, if any
+//!     vec![], // fields, if any
 //!     vec![] // children, if any
 //! );
 //! let mut out = String::with_capacity(114514);
@@ -19,30 +18,78 @@
 use std::{
     borrow::Cow,
     fmt::{Debug, Display},
-    iter::repeat,
+    iter::repeat_n,
 };
 
+use elision::ellipsis;
+
 pub type Str<'a> = Cow<'a, str>;
 /// This is recommended by `@rami3l` to supercede `Vec<Pretty>`.
 /// Why not use this? Because Rust wouldn't let me!
 /// https://github.com/rust-lang/rust/issues/23714
 #[allow(dead_code)]
 type Pretties<'a> = Cow<'a, [Pretty<'a>]>;
-/// Good kids don't do this.
-type BTreeMap<K, V> = Vec<(K, V)>;
 /// Cow-str associative array
-pub type CowAssocArr<'a> = BTreeMap<Str<'a>, Pretty<'a>>;
-pub type StrAssocArr<'a> = BTreeMap<&'a str, Pretty<'a>>;
+pub type CowAssocArr<'a> = FieldMap<'a>;
+/// Fields as they are given to the constructors.
+pub type StrAssocArr<'a> = Vec<(&'a str, Pretty<'a>)>;
 
 pub mod ascii;
+pub mod builder;
+pub mod dag;
+pub mod dedup;
+pub mod diff;
+pub mod dot;
+pub mod elision;
+pub mod expr;
+pub mod field_map;
+pub mod html;
+pub mod json;
+pub mod metrics;
+pub mod note;
+pub mod parse;
+pub mod parse_unicode;
+pub mod query;
+pub mod redact;
+pub mod scalar;
+pub mod seq;
+pub mod side_by_side;
+pub mod table;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod unicode;
+pub mod visit;
+pub mod weight;
+pub mod xml;
+
+pub use dag::RefError;
+pub use diff::Diff;
+pub use elision::Elision;
+pub use expr::Expr;
+pub use field_map::FieldMap;
+pub use parse::ParseError;
+pub use redact::Redaction;
+pub use scalar::Scalar;
+pub use seq::Seq;
+pub use table::Table;
+pub use weight::{Weight, WeightStyle};
 
 pub mod helper;
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct XmlNode<'a> {
     pub name: Str<'a>,
+    /// Records with an id can be referred to by [`Pretty::Ref`],
+    /// this is how DAGs are expressed.
+    pub id: Option<usize>,
     pub fields: CowAssocArr<'a>,
+    /// Runtime statistics like `rows=10k` or `12.3ms`, printed in
+    /// right-aligned columns next to the record.
+    pub metrics: Vec<Str<'a>>,
+    /// Shown as a bar or a percentage, see [`PrettyConfig::weights`].
+    pub weight: Option<Weight>,
+    /// Printed as `-- note`, see [`note`].
+    pub notes: Vec<Str<'a>>,
     /// Currently, if fields have `XmlNode` with children,
     /// they will not be considered during linearization.
     pub(crate) fields_is_linear: bool,
@@ -56,7 +103,7 @@ impl<'a> XmlNode<'a> {
         children: Vec<Pretty<'a>>,
     ) -> Self {
         let name = name.into();
-        let fields = fields.into_iter().map(|(k, v)| (k.into(), v)).collect();
+        let fields = fields.into_iter().collect();
         Self::new(name, fields, children)
     }
 
@@ -64,53 +111,145 @@ impl<'a> XmlNode<'a> {
         !self.children.is_empty() || (self.fields.iter()).any(|(_, x)| x.has_children())
     }
 
-    fn ol_build_str_ascii(&self, reduced_ws: bool, builder: &mut String) {
-        builder.push_str(&self.name);
-        if self.fields.is_empty() {
-            return;
+    /// The name, followed by the label if the record has an id.
+    pub(crate) fn header(&self) -> Str<'_> {
+        match self.id {
+            Some(id) => format!("{} [#{}]", self.name, id).into(),
+            None => Cow::Borrowed(&self.name),
+        }
+    }
+
+    /// Same record with different fields and children.
+    pub(crate) fn rebuild(&self, fields: CowAssocArr<'a>, children: Vec<Pretty<'a>>) -> Self {
+        Self {
+            id: self.id,
+            metrics: self.metrics.clone(),
+            weight: self.weight,
+            notes: self.notes.clone(),
+            ..Self::new(self.name.clone(), fields, children)
         }
-        builder.push_str(" { ");
-        for (i, (k, v)) in self.fields.iter().enumerate() {
-            if i > 0 {
-                builder.push_str(", ");
+    }
+
+    fn ol_build_str_ascii(&self, reduced_ws: bool, builder: &mut String) {
+        builder.push_str(&self.header());
+        if !self.fields.is_empty() {
+            builder.push_str(" { ");
+            for (i, (k, v)) in self.fields.iter().enumerate() {
+                if i > 0 {
+                    builder.push_str(", ");
+                }
+                if !v.is_elided() {
+                    builder.push_str(k);
+                    builder.push_str(": ");
+                }
+                v.ol_build_str_ascii(reduced_ws, builder);
             }
-            builder.push_str(k);
-            builder.push_str(": ");
-            v.ol_build_str_ascii(reduced_ws, builder);
+            builder.push_str(" }");
+        }
+        for note in self.note_lines() {
+            builder.push(' ');
+            builder.push_str(&note);
         }
-        builder.push_str(" }");
     }
 
     fn ol_len(&self, reduced_ws: bool) -> usize {
         let mem: usize = (self.fields.iter())
-            .map(|(k, v)| k.chars().count() + ": ".len() + v.ol_len(reduced_ws))
+            .map(|(k, v)| key_len(k, v) + v.ol_len(reduced_ws))
             .sum();
         let mid = self.fields.len().saturating_sub(1) * ", ".len();
         let begin_end = if self.fields.is_empty() {
             0
         } else {
             " {  }".len()
-        } + self.name.chars().count();
-        mem + mid + begin_end
+        } + display_width(&self.header());
+        let notes: usize = self
+            .note_lines()
+            .map(|n| " ".len() + display_width(&n))
+            .sum();
+        mem + mid + begin_end + notes
     }
 
     pub fn new(name: Str<'a>, fields: CowAssocArr<'a>, children: Vec<Pretty<'a>>) -> Self {
         Self {
             name,
+            id: None,
             fields,
+            metrics: vec![],
+            weight: None,
+            notes: vec![],
             fields_is_linear: false,
             children,
         }
     }
 }
 
+/// Number of columns taken by `s`.
+/// ANSI escape sequences (colors) take no space.
+pub(crate) fn display_width(s: &str) -> usize {
+    let mut chars = s.chars();
+    let mut width = 0;
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            width += 1;
+        } else if chars.next() == Some('[') {
+            // Skip until the final byte of the sequence, like `m`.
+            chars.by_ref().find(|c| ('@'..='~').contains(c));
+        }
+    }
+    width
+}
+
+/// Length of `key: `, elided fields have no key.
+pub(crate) fn key_len(k: &str, v: &Pretty) -> usize {
+    if v.is_elided() {
+        0
+    } else {
+        display_width(k) + ": ".len()
+    }
+}
+
+impl Pretty<'_> {
+    /// Whether the value of a field starts on the line after its key, in
+    /// which case the key is not padded, see [`PrettyConfig::align_keys`].
+    /// Tables always do, and so do broken seqs without an opening delimiter.
+    /// Broken arrays and maps do too when they are not `delimited`, since
+    /// nothing of them is left for the key line.
+    pub(crate) fn starts_below_key(&self, delimited: bool) -> bool {
+        match self {
+            Pretty::Array(v) => !delimited && !v.is_empty(),
+            Pretty::Map(m) => !delimited && !m.is_empty(),
+            Pretty::Seq(s) => s.open.is_empty() && !s.items.is_empty(),
+            Pretty::Table(_) => true,
+            _ => false,
+        }
+    }
+}
+
+pub(crate) fn ref_str(id: usize) -> String {
+    format!("→ see #{}", id)
+}
+
 /// Use `into`!!
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub enum Pretty<'a> {
     Text(Str<'a>),
+    /// A number, a boolean, `null`, a name or a string literal.
+    Scalar(Scalar<'a>),
     Record(XmlNode<'a>),
     Array(Vec<Self>),
+    /// Keys and values without a name, printed as `{ a: 1, b: 2 }`
+    /// or as a list of fields.
+    Map(FieldMap<'a>),
+    /// Like an array, but with its own delimiters and separator.
+    Seq(Seq<'a>),
     Linearized(&'a Self, usize),
+    /// Refers to the record with this id, printed as `→ see #id`.
+    Ref(usize),
+    /// This many elements, fields or children left out by [`Elision`],
+    /// printed as `… 3 more`.
+    Elided(usize),
+    /// Printed as a grid when it does not fit in one line.
+    Table(Table<'a>),
 }
 
 impl<'a> Pretty<'a> {
@@ -132,6 +271,16 @@ impl<'a> Pretty<'a> {
         Self::Array(list.iter().map(|&s| s.into()).collect())
     }
 
+    /// ```rust
+    /// use pretty_xmlish::Pretty;
+    /// let m = Pretty::simple_map(vec![("a", Pretty::int(1)), ("b", Pretty::int(2))]);
+    /// assert_eq!(m.to_one_line_string(false), "{ a: 1, b: 2 }");
+    /// assert_eq!(Pretty::parse_one_line("{ a: 1, b: 2 }").unwrap().to_one_line_string(true), "{a: 1, b: 2}");
+    /// ```
+    pub fn simple_map(fields: StrAssocArr<'a>) -> Self {
+        Self::Map(fields.into_iter().map(|(k, v)| (Str::from(k), v)).collect())
+    }
+
     pub fn display(display: &impl Display) -> Self {
         display.to_string().into()
     }
@@ -145,7 +294,10 @@ impl<'a> Pretty<'a> {
         match self {
             Record(xml) => xml.has_children(),
             Array(v) => v.iter().any(Self::has_children),
-            Text(..) => false,
+            Map(m) => m.values().any(Self::has_children),
+            Seq(s) => s.items.iter().any(Self::has_children),
+            // Cells of tables are printed in one line
+            Text(..) | Scalar(..) | Ref(..) | Elided(..) | Table(..) => false,
             // Note: linearization happens only when children are absent
             Linearized(..) => false,
         }
@@ -157,6 +309,7 @@ impl<'a> Pretty<'a> {
         use Pretty::*;
         match self {
             Text(s) => builder.push_str(s),
+            Scalar(s) => builder.push_str(&s.to_string()),
             Record(xml) => xml.ol_build_str_ascii(reduced_ws, builder),
             Array(v) => {
                 if v.is_empty() {
@@ -178,7 +331,35 @@ impl<'a> Pretty<'a> {
                 }
                 builder.push(']');
             }
+            Map(m) => {
+                if m.is_empty() {
+                    builder.push_str("{}");
+                    return;
+                }
+                builder.push('{');
+                if !reduced_ws {
+                    builder.push(' ');
+                }
+                for (i, (k, v)) in m.iter().enumerate() {
+                    if i > 0 {
+                        builder.push_str(", ");
+                    }
+                    if !v.is_elided() {
+                        builder.push_str(k);
+                        builder.push_str(": ");
+                    }
+                    v.ol_build_str_ascii(reduced_ws, builder);
+                }
+                if !reduced_ws {
+                    builder.push(' ');
+                }
+                builder.push('}');
+            }
+            Seq(s) => s.ol_build_str_ascii(reduced_ws, builder),
             Linearized(p, _) => p.ol_build_str_ascii(reduced_ws, builder),
+            Ref(id) => builder.push_str(&ref_str(*id)),
+            Elided(n) => builder.push_str(&ellipsis(*n)),
+            Table(t) => t.ol_build_str_ascii(reduced_ws, builder),
         }
     }
 
@@ -192,7 +373,8 @@ impl<'a> Pretty<'a> {
     pub(crate) fn ol_len(&self, reduced_ws: bool) -> usize {
         use Pretty::*;
         match self {
-            Text(s) => s.chars().count(),
+            Text(s) => display_width(s),
+            Scalar(s) => display_width(&s.to_string()),
             Record(xml) => xml.ol_len(reduced_ws),
             Array(v) => {
                 if v.is_empty() {
@@ -203,7 +385,22 @@ impl<'a> Pretty<'a> {
                 let beg = if reduced_ws { "[]".len() } else { "[  ]".len() };
                 mem + mid + beg
             }
+            Map(m) => {
+                if m.is_empty() {
+                    return "{}".len();
+                }
+                let mem: usize = (m.iter())
+                    .map(|(k, v)| key_len(k, v) + v.ol_len(reduced_ws))
+                    .sum();
+                let mid = (m.len() - 1) * ", ".len();
+                let beg = if reduced_ws { "{}".len() } else { "{  }".len() };
+                mem + mid + beg
+            }
+            Seq(s) => s.ol_len(reduced_ws),
             Linearized(_, len) => *len,
+            Ref(id) => display_width(&ref_str(*id)),
+            Elided(n) => display_width(&ellipsis(*n)),
+            Table(t) => t.ol_len(reduced_ws),
         }
     }
 }
@@ -223,16 +420,88 @@ pub struct PrettyConfig {
     /// If true, then there will not be space before record name and enclosed
     /// in lists.
     pub reduced_spaces: bool,
+    /// Too many elements, fields or children are elided in the middle.
+    pub elision: Elision,
+    /// Texts hidden before anything else, see [`Pretty::redact`].
+    pub redaction: Redaction,
+    /// Print identical subtrees once, see [`Pretty::dedup`].
+    pub dedup: bool,
+    /// Use ANSI colors, for terminals.
+    pub colors: bool,
+    /// Print arrays of records with the same fields as tables,
+    /// see [`Table::from_records`].
+    pub tables: bool,
+    /// When fields are printed one per line, pad the keys so that the
+    /// values start at the same column. Keys longer than this are not
+    /// taken into account, so one long key does not push everything right.
+    pub align_keys: Option<usize>,
+    /// Print [`XmlNode::metrics`] in a column on the right.
+    /// The weights are printed in that column too, so this also hides them.
+    pub show_metrics: bool,
+    /// How [`XmlNode::weight`] is printed after the metrics. They are only
+    /// printed with [`PrettyConfig::show_metrics`], and
+    /// [`WeightStyle::Hidden`] hides them but keeps the other metrics.
+    pub weights: WeightStyle,
 }
 
 impl PrettyConfig {
+    /// Tree transformations that happen before the layout.
+    pub(crate) fn preprocess<'b, 'a>(&self, pretty: &'b Pretty<'a>) -> Cow<'b, Pretty<'a>> {
+        // Relative to the whole tree, so before anything is elided.
+        let max_weight = pretty.max_weight();
+        let mut pretty = Cow::Borrowed(pretty);
+        if !self.redaction.is_noop() {
+            pretty = Cow::Owned(pretty.redact(&self.redaction));
+        }
+        if !self.elision.is_noop() {
+            pretty = Cow::Owned(pretty.elide(&self.elision));
+        }
+        // After elision, so that nothing refers to an elided subtree.
+        if self.dedup {
+            pretty = Cow::Owned(pretty.dedup());
+        }
+        // The weights are a column of the metrics.
+        if let Some(max) = max_weight.filter(|_| self.show_metrics) {
+            pretty = Cow::Owned(pretty.show_weights(self.weights, max, self.colors));
+        }
+        if self.colors && pretty.has_notes() {
+            pretty = Cow::Owned(pretty.into_owned().dim_notes());
+        }
+        pretty
+    }
+
+    /// For the exports, which are not read in a terminal.
+    pub(crate) fn preprocess_plain<'b, 'a>(&self, pretty: &'b Pretty<'a>) -> Cow<'b, Pretty<'a>> {
+        let config = PrettyConfig {
+            colors: false,
+            ..self.clone()
+        };
+        config.preprocess(pretty)
+    }
+
+    /// Same as [`Pretty::to_one_line_string`], after the elision, redaction
+    /// and deduplication asked for by `self`.
+    pub fn one_line(&self, out: &mut String, pretty: &Pretty) {
+        let pretty = self.preprocess(pretty);
+        pretty.ol_build_str_ascii(self.reduced_spaces, out);
+    }
+
+    /// Spaces after `key:` to align the values of the fields of `xml`.
+    pub(crate) fn key_pads<'b>(&self, xml: &'b XmlNode) -> impl Iterator<Item = usize> + 'b {
+        let widths = (xml.fields.iter()).map(|(k, v)| (!v.is_elided()).then(|| display_width(k)));
+        let column = (self.align_keys)
+            .and_then(|cap| widths.clone().flatten().filter(|&w| w <= cap).max())
+            .unwrap_or(0);
+        widths.map(move |w| w.map_or(0, |w| column.saturating_sub(w)))
+    }
+
     pub fn horizon(&self, out: &mut String, width: usize) {
         if !self.need_boundaries {
             return;
         }
-        out.push_str("+");
-        out.extend(repeat("-").take(width + 2));
-        out.push_str("+");
+        out.push('+');
+        out.extend(repeat_n("-", width + 2));
+        out.push('+');
     }
 }
 
@@ -242,6 +511,10 @@ struct LinedBuffer<'a> {
     pub already_occupied: usize,
     out: &'a mut String,
     config: &'a PrettyConfig,
+    /// See [`PrettyConfig::metric_columns`].
+    metric_columns: Vec<usize>,
+    /// Metrics to put at the end of the current line.
+    pending_metrics: Option<String>,
 }
 impl<'a> LinedBuffer<'a> {
     fn begin_line(&mut self) {
@@ -251,7 +524,7 @@ impl<'a> LinedBuffer<'a> {
     }
     fn push(&mut self, s: &str) {
         self.out.push_str(s);
-        self.already_occupied += s.chars().count();
+        self.already_occupied += display_width(s);
     }
     fn pip(&mut self, amount: usize) {
         self.push(" ".repeat(amount).as_str());
@@ -264,6 +537,7 @@ impl<'a> LinedBuffer<'a> {
         //     );
         //     self.push(" |\n");
         // } else {
+        self.push_metrics();
         let eol = if self.config.need_boundaries {
             self.pip(self.width - self.already_occupied);
             " |\n"
@@ -283,6 +557,14 @@ impl Default for PrettyConfig {
             width: 120,
             need_boundaries: true,
             reduced_spaces: false,
+            elision: Elision::default(),
+            redaction: Redaction::default(),
+            dedup: false,
+            colors: false,
+            tables: false,
+            align_keys: None,
+            show_metrics: true,
+            weights: Default::default(),
         }
     }
 }
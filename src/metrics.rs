@@ -0,0 +1,136 @@
+use std::iter::repeat_n;
+
+use crate::{display_width, LinedBuffer, Pretty, PrettyConfig, XmlNode};
+
+/// Between the tree and the metrics, and between two metrics.
+const GAP: usize = 2;
+
+fn widen(xml: &XmlNode, widths: &mut Vec<usize>) {
+    for (i, m) in xml.metrics.iter().enumerate() {
+        match widths.get_mut(i) {
+            Some(w) => *w = (*w).max(display_width(m)),
+            None => widths.push(display_width(m)),
+        }
+    }
+}
+
+fn collect_widths(pretty: &Pretty, widths: &mut Vec<usize>) {
+    use Pretty::*;
+    match pretty {
+        Record(xml) => {
+            widen(xml, widths);
+            xml.fields
+                .iter()
+                .for_each(|(_, v)| collect_widths(v, widths));
+            xml.children.iter().for_each(|c| collect_widths(c, widths));
+        }
+        Array(v) => v.iter().for_each(|p| collect_widths(p, widths)),
+        Map(m) => m.values().for_each(|p| collect_widths(p, widths)),
+        Seq(s) => s.items.iter().for_each(|p| collect_widths(p, widths)),
+        Table(t) => t
+            .rows
+            .iter()
+            .flatten()
+            .for_each(|p| collect_widths(p, widths)),
+        Linearized(p, _) => collect_widths(p, widths),
+        Text(..) | Scalar(..) | Elided(..) | Ref(..) => {}
+    }
+}
+
+/// Same for the records that start a line once `pretty` is laid out, the
+/// only ones whose metrics are printed. Those of the records inside a
+/// line, like in a field printed on one line, are not.
+fn collect_printed_widths(pretty: &Pretty, linear_fields: bool, widths: &mut Vec<usize>) {
+    use Pretty::*;
+    let subtrees: Vec<&Pretty> = match pretty {
+        Record(xml) => {
+            widen(xml, widths);
+            let fields = (xml.fields.values()).filter(|_| !(linear_fields && xml.fields_is_linear));
+            fields.chain(&xml.children).collect()
+        }
+        Array(v) => v.iter().collect(),
+        Map(m) => m.values().collect(),
+        Seq(s) => s.items.iter().collect(),
+        Linearized(Record(xml), _) => {
+            widen(xml, widths);
+            vec![]
+        }
+        Linearized(p, _) => vec![p],
+        Table(..) | Text(..) | Scalar(..) | Elided(..) | Ref(..) => vec![],
+    };
+    for p in subtrees {
+        collect_printed_widths(p, linear_fields, widths);
+    }
+}
+
+/// The most metrics a record of `pretty` has.
+pub(crate) fn metrics_count(pretty: &Pretty) -> usize {
+    let mut widths = vec![];
+    collect_widths(pretty, &mut widths);
+    widths.len()
+}
+
+/// Width of the metrics column, including the gap before it.
+pub(crate) fn metrics_width(columns: &[usize]) -> usize {
+    columns.iter().map(|w| w + GAP).sum()
+}
+
+impl PrettyConfig {
+    /// Width reserved for the metrics columns before the layout, enough
+    /// for the metrics of every record.
+    pub(crate) fn reserved_metrics_width(&self, pretty: &Pretty) -> usize {
+        let mut widths = vec![];
+        if self.show_metrics {
+            collect_widths(pretty, &mut widths);
+        }
+        metrics_width(&widths)
+    }
+
+    /// Widths of the metrics columns, one per position in
+    /// [`XmlNode::metrics`], or nothing if there is no metric to show.
+    /// `laid_out` is the result of the layout, where `linear_fields` tells
+    /// whether the records whose fields fit print them on their first line.
+    pub(crate) fn metric_columns(&self, laid_out: &Pretty, linear_fields: bool) -> Vec<usize> {
+        let mut widths = vec![];
+        if self.show_metrics {
+            collect_printed_widths(laid_out, linear_fields, &mut widths);
+        }
+        widths
+    }
+}
+
+impl LinedBuffer<'_> {
+    /// The metrics of `xml` go to the right end of the current line.
+    pub(crate) fn metrics_of(&mut self, xml: &XmlNode) {
+        if self.metric_columns.is_empty() || xml.metrics.is_empty() {
+            return;
+        }
+        let mut s = String::new();
+        for (i, w) in self.metric_columns.iter().enumerate() {
+            let m = xml.metrics.get(i).map_or("", |m| m);
+            s.extend(repeat_n(' ', GAP + w - display_width(m)));
+            s.push_str(m);
+        }
+        // Missing metrics at the end leave no trailing spaces
+        s.truncate(s.trim_end().len());
+        self.pending_metrics = Some(s);
+    }
+
+    /// Same for a record printed on one line, which the second layout
+    /// pass may have wrapped more than once.
+    pub(crate) fn metrics_of_linearized(&mut self, pretty: &Pretty) {
+        match pretty {
+            Pretty::Record(xml) => self.metrics_of(xml),
+            Pretty::Linearized(p, _) => self.metrics_of_linearized(p),
+            _ => {}
+        }
+    }
+
+    pub(crate) fn push_metrics(&mut self) {
+        if let Some(m) = self.pending_metrics.take() {
+            let tree_width = self.width - metrics_width(&self.metric_columns);
+            self.pip(tree_width.saturating_sub(self.already_occupied));
+            self.push(&m);
+        }
+    }
+}
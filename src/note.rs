@@ -0,0 +1,47 @@
+//! Remarks on records, like optimizer hints or warnings.
+//!
+//! ```rust
+//! use pretty_xmlish::Pretty;
+//! let scan = Pretty::record("BatchScan")
+//!     .field("table", "t1")
+//!     .note("missing statistics")
+//!     .build();
+//! assert_eq!(scan.to_one_line_string(false), "BatchScan { table: t1 } -- missing statistics");
+//! ```
+//!
+//! A record printed in one line is followed by its notes.
+//! Otherwise, every note takes a line right after the name of the record.
+//! The XML export writes them as comments, and the DOT and HTML exports
+//! as the tooltips of the records.
+
+use crate::{Pretty, XmlNode};
+
+pub(crate) const NOTE_MARK: &str = "-- ";
+
+/// Dimmed, but with the same width.
+fn dim(note: &str) -> String {
+    format!("\x1b[2m{}\x1b[0m", note)
+}
+
+impl XmlNode<'_> {
+    /// Each note with its mark.
+    pub(crate) fn note_lines(&self) -> impl Iterator<Item = String> + '_ {
+        (self.notes.iter()).map(|note| format!("{}{}", NOTE_MARK, note))
+    }
+}
+
+impl<'a> Pretty<'a> {
+    pub(crate) fn has_notes(&self) -> bool {
+        (self.pre_order()).any(|(_, p)| matches!(p, Pretty::Record(xml) if !xml.notes.is_empty()))
+    }
+
+    /// For terminals, see [`crate::PrettyConfig::colors`].
+    pub(crate) fn dim_notes(self) -> Self {
+        self.map_records(|mut xml| {
+            for note in xml.notes.iter_mut() {
+                *note = dim(note).into();
+            }
+            xml
+        })
+    }
+}
@@ -0,0 +1,262 @@
+//! Reads back what [`Pretty::to_one_line_string`] prints.
+//!
+//! ```rust
+//! use pretty_xmlish::Pretty;
+//! let scan = Pretty::childless_record("BatchScan", vec![
+//!     ("table", "t1".into()),
+//!     ("columns", Pretty::list_of_strings(&["v1", "v2"])),
+//!     ("predicate", "(f($0, $1) = 1)".into()),
+//! ]);
+//! for reduced_ws in [false, true] {
+//!     let s = scan.to_one_line_string(reduced_ws);
+//!     assert_eq!(Pretty::parse_one_line(&s).unwrap(), scan);
+//! }
+//! ```
+//!
+//! The format has no quoting, so some trees cannot be told apart:
+//! a record without fields prints like a text and is read as a text,
+//! texts with unbalanced brackets, `, ` or surrounding spaces
+//! do not survive the round trip, and [`crate::Seq`]s are read as texts.
+//! The notes after a record with fields are read back, split at ` -- `,
+//! with the same limits as texts. Those of a record without fields are
+//! part of the text it is read as.
+//!
+//! [`Pretty::Scalar`]s print like texts too, and as `10` may have been
+//! either, they are read back as texts rather than guessed:
+//!
+//! ```rust
+//! use pretty_xmlish::Pretty;
+//! let limit = Pretty::record("Limit").field("limit", Pretty::int(10)).build();
+//! let text = Pretty::record("Limit").field("limit", "10").build();
+//! assert_eq!(Pretty::parse_one_line("Limit { limit: 10 }").unwrap(), text);
+//! assert_ne!(text, limit);
+//! ```
+
+use std::fmt::{Display, Formatter};
+
+use crate::{note::NOTE_MARK, Pretty, Str, XmlNode};
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseError {
+    /// Byte offset in the input.
+    pub pos: usize,
+    pub message: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.pos)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub type ParseResult<T> = Result<T, ParseError>;
+
+struct Parser<'s> {
+    src: &'s str,
+    pos: usize,
+}
+
+impl<'s> Parser<'s> {
+    fn rest(&self) -> &'s str {
+        &self.src[self.pos..]
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> ParseResult<T> {
+        Err(ParseError {
+            pos: self.pos,
+            message: message.into(),
+        })
+    }
+
+    fn skip_spaces(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start_matches(' ').len();
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        let ok = self.rest().starts_with(s);
+        if ok {
+            self.pos += s.len();
+        }
+        ok
+    }
+
+    fn expect(&mut self, s: &str) -> ParseResult<()> {
+        if self.eat(s) {
+            Ok(())
+        } else {
+            self.error(format!("expected `{}`", s))
+        }
+    }
+
+    /// Scans a text until `,`, `]` or `}` outside of brackets.
+    /// With `stop_at_brace`, also stops before a `{` that opens fields.
+    fn scan(&mut self, stop_at_brace: bool) -> ParseResult<&'s str> {
+        let start = self.pos;
+        let mut depth = 0usize;
+        for (i, c) in self.rest().char_indices() {
+            match c {
+                '{' if depth == 0 && stop_at_brace => {
+                    self.pos = start + i;
+                    return Ok(&self.src[start..self.pos]);
+                }
+                '(' | '[' | '{' => depth += 1,
+                ',' | ']' | '}' if depth == 0 => {
+                    self.pos = start + i;
+                    return Ok(&self.src[start..self.pos]);
+                }
+                ')' | ']' | '}' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        if depth > 0 {
+            self.pos = self.src.len();
+            return self.error("unclosed bracket");
+        }
+        self.pos = self.src.len();
+        Ok(&self.src[start..])
+    }
+
+    fn value(&mut self) -> ParseResult<Pretty<'s>> {
+        if self.eat("[") {
+            return self.array();
+        }
+        let start = self.pos;
+        let head = self.scan(true)?;
+        if !self.rest().starts_with('{') {
+            let text = head.trim_end_matches(' ');
+            if text.is_empty() {
+                self.pos = start;
+                return self.error("expected a value");
+            }
+            let marker = parse_ref(text).or_else(|| parse_elided(text));
+            return Ok(marker.unwrap_or(Pretty::Text(text.into())));
+        }
+        let header = head.trim_end_matches(' ');
+        if header.is_empty() {
+            return self.map();
+        }
+        let (name, id) = parse_header(header);
+        self.expect("{")?;
+        let fields = self.fields()?;
+        let mut xml = XmlNode::new(name.into(), fields.into(), vec![]);
+        xml.id = id;
+        xml.notes = self.notes()?;
+        Ok(Pretty::Record(xml))
+    }
+
+    /// ` -- a -- b` after the fields of a record.
+    fn notes(&mut self) -> ParseResult<Vec<Str<'s>>> {
+        let mut notes = vec![];
+        if self.eat(" ") {
+            if !self.rest().starts_with(NOTE_MARK) {
+                self.pos -= 1;
+                return Ok(notes);
+            }
+            let text = self.scan(false)?.trim_end_matches(' ');
+            let mut rest = text.get(NOTE_MARK.len()..).unwrap_or("");
+            while let Some((note, next)) = rest.split_once(" -- ") {
+                notes.push(note.into());
+                rest = next;
+            }
+            notes.push(rest.into());
+        }
+        Ok(notes)
+    }
+
+    fn array(&mut self) -> ParseResult<Pretty<'s>> {
+        self.skip_spaces();
+        let mut v = vec![];
+        if self.eat("]") {
+            return Ok(Pretty::Array(v));
+        }
+        loop {
+            v.push(self.value()?);
+            self.skip_spaces();
+            if self.eat("]") {
+                return Ok(Pretty::Array(v));
+            }
+            self.expect(",")?;
+            self.skip_spaces();
+        }
+    }
+
+    /// Fields without a record name.
+    fn map(&mut self) -> ParseResult<Pretty<'s>> {
+        self.expect("{")?;
+        self.skip_spaces();
+        if self.eat("}") {
+            return Ok(Pretty::Map(Default::default()));
+        }
+        Ok(Pretty::Map(self.fields()?.into()))
+    }
+
+    fn fields(&mut self) -> ParseResult<Vec<(Str<'s>, Pretty<'s>)>> {
+        let mut fields = vec![];
+        loop {
+            self.skip_spaces();
+            fields.push(self.field()?);
+            self.skip_spaces();
+            if self.eat("}") {
+                return Ok(fields);
+            }
+            self.expect(",")?;
+        }
+    }
+
+    fn field(&mut self) -> ParseResult<(Str<'s>, Pretty<'s>)> {
+        let rest = self.rest();
+        let key_end = rest.find([':', ',', '}', '[', '{', '(']);
+        match key_end {
+            Some(end) if rest[end..].starts_with(": ") => {
+                self.pos += end + ": ".len();
+                Ok((rest[..end].into(), self.value()?))
+            }
+            // Elided fields have no key
+            _ => Ok(("".into(), self.value()?)),
+        }
+    }
+}
+
+/// `Name [#1]` is a record with an id.
+pub(crate) fn parse_header(header: &str) -> (&str, Option<usize>) {
+    let id = (header.strip_suffix(']'))
+        .and_then(|h| h.rsplit_once(" [#"))
+        .and_then(|(name, id)| Some((name, id.parse().ok()?)));
+    match id {
+        Some((name, id)) => (name, Some(id)),
+        None => (header, None),
+    }
+}
+
+fn parse_ref<'s>(text: &str) -> Option<Pretty<'s>> {
+    let id = text.strip_prefix("→ see #")?.parse().ok()?;
+    Some(Pretty::Ref(id))
+}
+
+/// `… 3 more`
+fn parse_elided<'s>(text: &str) -> Option<Pretty<'s>> {
+    let hidden = text
+        .strip_prefix("… ")?
+        .strip_suffix(" more")?
+        .parse()
+        .ok()?;
+    Some(Pretty::Elided(hidden))
+}
+
+impl<'s> Pretty<'s> {
+    /// Parses the output of [`Pretty::to_one_line_string`],
+    /// with or without `reduced_ws`. See the [module docs](crate::parse).
+    pub fn parse_one_line(src: &'s str) -> ParseResult<Self> {
+        let mut parser = Parser { src, pos: 0 };
+        parser.skip_spaces();
+        let pretty = parser.value()?;
+        parser.skip_spaces();
+        if !parser.rest().is_empty() {
+            return parser.error("unexpected trailing input");
+        }
+        Ok(pretty)
+    }
+}
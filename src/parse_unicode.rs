@@ -0,0 +1,229 @@
+//! Reads back what [`PrettyConfig::unicode`](crate::PrettyConfig::unicode) prints.
+//!
+//! ```rust
+//! use pretty_xmlish::{Pretty, PrettyConfig};
+//! let plan = Pretty::simple_record("BatchExchange",
+//!     vec![("order", Pretty::Array(vec![])), ("dist", "Single".into())],
+//!     vec![Pretty::childless_record("BatchScan", vec![("table", "t1".into())])],
+//! );
+//! let mut out = String::new();
+//! PrettyConfig::default().unicode(&mut out, &plan);
+//! assert_eq!(Pretty::parse_unicode(&out).unwrap(), plan);
+//! ```
+//!
+//! The indentation is found from the first indented line, so the output of
+//! any `indent` and `reduced_spaces` can be read. The same ambiguities as in
+//! [`crate::parse`] apply. In addition, a child that is a text looking like
+//! `key: value` right after the fields is read as a field, arrays with
+//! a single element are read as that element, and arrays whose elements all
+//! look like `key: value` are read as a [`Pretty::Map`], the way a map
+//! broken into lines is printed. Lines starting with `-- ` before the
+//! fields and the children of a record are its notes.
+
+use crate::{
+    note::NOTE_MARK,
+    parse::{parse_header, ParseError, ParseResult},
+    Pretty, XmlNode,
+};
+
+struct Line<'s> {
+    depth: usize,
+    content: &'s str,
+    /// Byte offset of `content` in the input.
+    offset: usize,
+}
+
+/// Box-drawing characters and spaces that make up the tree art.
+fn is_art(c: char) -> bool {
+    "│├└┌─ ".contains(c)
+}
+
+fn offset_in(src: &str, part: &str) -> usize {
+    part.as_ptr() as usize - src.as_ptr() as usize
+}
+
+/// Removes the boundary box if there is one.
+fn strip_box(src: &str) -> Vec<&str> {
+    let lines: Vec<_> = src.lines().map(str::trim_end).collect();
+    let is_horizon = |l: &str| {
+        let dashes = l.strip_prefix('+').and_then(|l| l.strip_suffix('+'));
+        dashes.is_some_and(|d| d.chars().all(|c| c == '-'))
+    };
+    match (lines.first(), lines.last()) {
+        (Some(fst), Some(lst)) if lines.len() > 1 && is_horizon(fst) && is_horizon(lst) => {
+            (lines[1..lines.len() - 1].iter())
+                .map(|l| {
+                    let l = l.strip_prefix("| ").unwrap_or(l);
+                    l.strip_suffix('|').unwrap_or(l).trim_end()
+                })
+                .collect()
+        }
+        _ => lines,
+    }
+}
+
+struct TreeParser<'s> {
+    src: &'s str,
+    lines: Vec<Line<'s>>,
+}
+
+impl<'s> TreeParser<'s> {
+    fn new(src: &'s str) -> ParseResult<Self> {
+        let raw = strip_box(src);
+        let mut lines = Vec::with_capacity(raw.len());
+        // Width of one level of indentation, found from the first indented line.
+        let mut unit = None;
+        for (i, l) in raw.into_iter().enumerate() {
+            let content = l.trim_start_matches(is_art);
+            let offset = offset_in(src, content);
+            let width = l[..l.len() - content.len()].chars().count();
+            if i > 0 && content.is_empty() && width == 0 {
+                continue;
+            }
+            let depth = match (width, unit) {
+                (0, _) => 0,
+                (w, None) => {
+                    unit = Some(w);
+                    1
+                }
+                (w, Some(u)) if w % u == 0 => w / u,
+                _ => {
+                    return Err(ParseError {
+                        pos: offset,
+                        message: "inconsistent indentation".into(),
+                    })
+                }
+            };
+            if i > 0 && depth == 0 {
+                return Err(ParseError {
+                    pos: offset,
+                    message: "more than one root".into(),
+                });
+            }
+            lines.push(Line {
+                depth,
+                content,
+                offset,
+            });
+        }
+        Ok(Self { src, lines })
+    }
+
+    fn error<T>(&self, line: usize, message: &str) -> ParseResult<T> {
+        let pos = self.lines.get(line).map_or(self.src.len(), |l| l.offset);
+        Err(ParseError {
+            pos,
+            message: message.into(),
+        })
+    }
+
+    fn one_line(&self, content: &'s str) -> ParseResult<Pretty<'s>> {
+        Pretty::parse_one_line(content).map_err(|e| ParseError {
+            pos: e.pos + offset_in(self.src, content),
+            ..e
+        })
+    }
+
+    /// Lines directly below line `i`.
+    fn subs(&self, i: usize) -> ParseResult<Vec<usize>> {
+        let depth = self.lines[i].depth;
+        let mut subs = vec![];
+        let mut j = i + 1;
+        while j < self.lines.len() && self.lines[j].depth > depth {
+            if self.lines[j].depth != depth + 1 {
+                return self.error(j, "unexpected indentation");
+            }
+            subs.push(j);
+            j += 1;
+            while j < self.lines.len() && self.lines[j].depth > depth + 1 {
+                j += 1;
+            }
+        }
+        Ok(subs)
+    }
+
+    /// `content` is line `i`, or the value part of it if it is a field.
+    fn node(&self, i: usize, content: &'s str) -> ParseResult<Pretty<'s>> {
+        let subs = self.subs(i)?;
+        if subs.is_empty() {
+            return self.one_line(content);
+        }
+        if content.is_empty() {
+            // A broken map, if the lines are fields
+            let entries = (subs.iter())
+                .map(|&j| {
+                    let content = self.lines[j].content;
+                    split_field(content)
+                        .or_else(|| content.starts_with("… ").then_some(("", content)))
+                })
+                .collect::<Option<Vec<_>>>();
+            if let Some(entries) = entries.filter(|e| e.iter().any(|(k, _)| !k.is_empty())) {
+                let map = (subs.iter().zip(entries))
+                    .map(|(&j, (k, v))| Ok((k, self.node(j, v)?)))
+                    .collect::<ParseResult<Vec<_>>>()?;
+                return Ok(Pretty::Map(map.into()));
+            }
+            let list = subs
+                .into_iter()
+                .map(|j| self.node(j, self.lines[j].content));
+            return Ok(Pretty::Array(list.collect::<ParseResult<_>>()?));
+        }
+        // Fields are either on the header line, or one per line before the children.
+        let mut xml = match self.one_line(content)? {
+            Pretty::Record(xml) => XmlNode {
+                fields_is_linear: true,
+                ..xml
+            },
+            _ => {
+                let (name, id) = parse_header(content);
+                XmlNode {
+                    id,
+                    ..XmlNode::new(name.into(), Default::default(), vec![])
+                }
+            }
+        };
+        for j in subs {
+            let content = self.lines[j].content;
+            // Notes come right after the header, before the fields
+            let is_header_part = xml.fields.is_empty() && xml.children.is_empty();
+            if let Some(note) = content.strip_prefix(NOTE_MARK).filter(|_| is_header_part) {
+                xml.notes.push(note.into());
+                continue;
+            }
+            match split_field(content) {
+                Some((k, v)) if !xml.fields_is_linear && xml.children.is_empty() => {
+                    xml.fields.insert(k.into(), self.node(j, v)?);
+                }
+                _ => xml.children.push(self.node(j, content)?),
+            }
+        }
+        xml.fields_is_linear = false;
+        Ok(Pretty::Record(xml))
+    }
+}
+
+/// `key: value` or `key:`, where the key is a single word.
+pub(crate) fn split_field(content: &str) -> Option<(&str, &str)> {
+    let (k, v) = content.split_once(':')?;
+    let is_key = !k.is_empty() && !k.contains(|c: char| c.is_whitespace() || "{}[](),".contains(c));
+    if !is_key {
+        return None;
+    }
+    match v.strip_prefix(' ') {
+        Some(v) => Some((k, v)),
+        None if v.is_empty() => Some((k, v)),
+        None => None,
+    }
+}
+
+impl<'s> Pretty<'s> {
+    /// Parses the unicode tree art, with or without the boundaries.
+    /// See the [module docs](crate::parse_unicode).
+    pub fn parse_unicode(src: &'s str) -> ParseResult<Self> {
+        let parser = TreeParser::new(src)?;
+        if parser.lines.is_empty() {
+            return parser.error(0, "expected a tree");
+        }
+        parser.node(0, parser.lines[0].content)
+    }
+}
@@ -0,0 +1,304 @@
+//! A small XPath-like language to select parts of a tree.
+//!
+//! ```rust
+//! use pretty_xmlish::Pretty;
+//! let plan = Pretty::simple_record("Join", vec![("type", "Inner".into())], vec![
+//!     Pretty::childless_record("BatchScan", vec![
+//!         ("table", "t1".into()),
+//!         ("columns", Pretty::list_of_strings(&["v1", "v2"])),
+//!     ]),
+//!     Pretty::childless_record("BatchScan", vec![("table", "t2".into())]),
+//! ]);
+//! let found = plan.select("//BatchScan[@table='t1']/@columns").unwrap();
+//! assert_eq!(found.len(), 1);
+//! assert_eq!(found[0].1, &Pretty::list_of_strings(&["v1", "v2"]));
+//! assert_eq!(plan.select("/Join/BatchScan[2]/@table").unwrap()[0].1, &"t2".into());
+//! ```
+//!
+//! A query is a sequence of steps. `/test` looks at the sub-trees of the
+//! current nodes and `//test` at all their descendants, the first step
+//! starting above the root. The sub-trees of a record are its fields and
+//! its children, those of an array its elements. The tests are:
+//!
+//! + `Name`, the records with this name, be they children, elements or
+//!   the values of fields,
+//! + `@key`, the values of the fields with this key,
+//! + `*`, every sub-tree.
+//!
+//! Each test may be followed by conditions in brackets: `[@key]` for the
+//! records that have this field, `[@key='value']` for the records where
+//! the field prints as `value` on one line (string literals without their
+//! quotes), and `[2]` for the second of the nodes found from the same node.
+//! A query without the leading `/` starts at the root, so `Name` is a
+//! sub-tree of the root.
+
+use std::collections::HashSet;
+
+use crate::{
+    parse::{ParseError, ParseResult},
+    visit::{Path, Step},
+    Pretty,
+};
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Test {
+    Name(String),
+    Field(String),
+    Any,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Condition {
+    HasField(String),
+    FieldIs(String, String),
+    /// 1-based, like in XPath.
+    Position(usize),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct QueryStep {
+    descendants: bool,
+    test: Test,
+    conditions: Vec<Condition>,
+}
+
+/// A parsed query, see the [module docs](crate::query).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Query {
+    /// Whether the first step starts above the root.
+    absolute: bool,
+    steps: Vec<QueryStep>,
+}
+
+/// A selected tree and the path to it from the root.
+pub type Selected<'b, 'a> = (Path<'a>, &'b Pretty<'a>);
+
+struct QueryParser<'s> {
+    src: &'s str,
+    pos: usize,
+}
+
+fn is_name_char(c: char) -> bool {
+    !"/[]@='\"*".contains(c) && !c.is_whitespace()
+}
+
+impl<'s> QueryParser<'s> {
+    fn rest(&self) -> &'s str {
+        &self.src[self.pos..]
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> ParseResult<T> {
+        Err(ParseError {
+            pos: self.pos,
+            message: message.into(),
+        })
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        let ok = self.rest().starts_with(s);
+        if ok {
+            self.pos += s.len();
+        }
+        ok
+    }
+
+    fn expect(&mut self, s: &str) -> ParseResult<()> {
+        if self.eat(s) {
+            Ok(())
+        } else {
+            self.error(format!("expected `{}`", s))
+        }
+    }
+
+    fn name(&mut self) -> ParseResult<String> {
+        let rest = self.rest();
+        let len = rest.find(|c| !is_name_char(c)).unwrap_or(rest.len());
+        if len == 0 {
+            return self.error("expected a name");
+        }
+        self.pos += len;
+        Ok(rest[..len].into())
+    }
+
+    fn test(&mut self) -> ParseResult<Test> {
+        if self.eat("*") {
+            Ok(Test::Any)
+        } else if self.eat("@") {
+            Ok(Test::Field(self.name()?))
+        } else {
+            Ok(Test::Name(self.name()?))
+        }
+    }
+
+    fn quoted(&mut self) -> ParseResult<String> {
+        for quote in ["'", "\""] {
+            if self.eat(quote) {
+                return match self.rest().find(quote) {
+                    Some(i) => {
+                        let s = self.rest()[..i].into();
+                        self.pos += i + 1;
+                        Ok(s)
+                    }
+                    None => self.error("unclosed string"),
+                };
+            }
+        }
+        self.error("expected a quoted string")
+    }
+
+    fn condition(&mut self) -> ParseResult<Condition> {
+        let condition = if self.eat("@") {
+            let key = self.name()?;
+            if self.eat("=") {
+                Condition::FieldIs(key, self.quoted()?)
+            } else {
+                Condition::HasField(key)
+            }
+        } else {
+            let rest = self.rest();
+            let len = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            match rest[..len].parse() {
+                Ok(n) if n > 0 => {
+                    self.pos += len;
+                    Condition::Position(n)
+                }
+                _ => return self.error("expected `@key` or a position"),
+            }
+        };
+        self.expect("]")?;
+        Ok(condition)
+    }
+
+    fn step(&mut self, descendants: bool) -> ParseResult<QueryStep> {
+        let test = self.test()?;
+        let mut conditions = vec![];
+        while self.eat("[") {
+            conditions.push(self.condition()?);
+        }
+        Ok(QueryStep {
+            descendants,
+            test,
+            conditions,
+        })
+    }
+}
+
+impl Query {
+    pub fn parse(src: &str) -> ParseResult<Self> {
+        let mut parser = QueryParser { src, pos: 0 };
+        let mut steps = vec![];
+        let absolute = parser.rest().starts_with('/');
+        if !absolute {
+            steps.push(parser.step(false)?);
+        }
+        while !parser.rest().is_empty() {
+            if parser.eat("//") {
+                steps.push(parser.step(true)?);
+            } else if parser.eat("/") {
+                steps.push(parser.step(false)?);
+            } else {
+                return parser.error("expected `/`");
+            }
+        }
+        if steps.is_empty() {
+            return parser.error("expected a step");
+        }
+        Ok(Self { absolute, steps })
+    }
+
+    /// The matches in the order of the tree, parents first.
+    pub fn select<'b, 'a>(&self, root: &'b Pretty<'a>) -> Vec<Selected<'b, 'a>> {
+        let mut current = vec![];
+        let mut steps = self.steps.iter();
+        if self.absolute {
+            // Above the root, the only sub-tree is the root itself.
+            let step = steps.next().unwrap();
+            let candidates = if step.descendants {
+                root.pre_order().collect()
+            } else {
+                vec![(vec![], root)]
+            };
+            current = step.filter(candidates);
+        } else {
+            current.push((vec![], root));
+        }
+        for step in steps {
+            let mut seen = HashSet::new();
+            current = (current.into_iter())
+                .flat_map(|(path, p)| step.filter(step.candidates(path, p)))
+                .filter(|(_, p)| seen.insert(*p as *const Pretty))
+                .collect();
+        }
+        current
+    }
+}
+
+impl QueryStep {
+    fn candidates<'b, 'a>(&self, path: Path<'a>, pretty: &'b Pretty<'a>) -> Vec<Selected<'b, 'a>> {
+        let below = |(mut sub_path, sub): Selected<'b, 'a>| {
+            let mut full = path.clone();
+            full.append(&mut sub_path);
+            (full, sub)
+        };
+        if self.descendants {
+            pretty.pre_order().skip(1).map(below).collect()
+        } else {
+            (pretty.subtrees().into_iter())
+                .map(|(step, sub)| below((vec![step], sub)))
+                .collect()
+        }
+    }
+
+    fn filter<'b, 'a>(&self, candidates: Vec<Selected<'b, 'a>>) -> Vec<Selected<'b, 'a>> {
+        let mut found: Vec<_> = (candidates.into_iter())
+            .filter(|(path, p)| self.test.accepts(path.last(), p))
+            .collect();
+        for condition in &self.conditions {
+            found = match condition {
+                Condition::Position(n) => found.into_iter().nth(n - 1).into_iter().collect(),
+                _ => found
+                    .into_iter()
+                    .filter(|(_, p)| condition.holds(p))
+                    .collect(),
+            };
+        }
+        found
+    }
+}
+
+impl Test {
+    fn accepts(&self, step: Option<&Step>, pretty: &Pretty) -> bool {
+        match (self, step) {
+            (Test::Any, _) => true,
+            (Test::Field(k), Some(Step::Field(key))) => k == key,
+            (Test::Field(_), _) => false,
+            (Test::Name(n), _) => pretty.name() == Some(n),
+        }
+    }
+}
+
+impl Condition {
+    fn holds(&self, pretty: &Pretty) -> bool {
+        let Pretty::Record(xml) = pretty else {
+            return false;
+        };
+        match self {
+            Condition::HasField(key) => xml.fields.contains_key(key),
+            Condition::FieldIs(key, value) => match xml.fields.get(key) {
+                Some(Pretty::Scalar(s)) => s.raw() == *value,
+                Some(v) => v.to_one_line_string(false) == *value,
+                None => false,
+            },
+            Condition::Position(..) => true,
+        }
+    }
+}
+
+impl<'a> Pretty<'a> {
+    /// Parses `query` and selects from this tree, see the [module docs](crate::query).
+    pub fn select<'b>(&'b self, query: &str) -> ParseResult<Vec<Selected<'b, 'a>>> {
+        Ok(Query::parse(query)?.select(self))
+    }
+}
@@ -0,0 +1,162 @@
+//! Hides the values of a tree, for plans that are shared outside.
+//!
+//! ```rust
+//! use pretty_xmlish::{redact::Redaction, Pretty};
+//! let scan = Pretty::childless_record("BatchScan", vec![
+//!     ("table", "t1".into()),
+//!     ("predicate", "(name = 'alice')".into()),
+//!     ("columns", Pretty::list_of_strings(&["id", "password"])),
+//! ]);
+//! let redaction = Redaction {
+//!     keys: vec!["pred*".into()],
+//!     ..Redaction::default()
+//! }
+//! .with_predicate(|v| v.contains("pass"));
+//! assert_eq!(
+//!     scan.redact(&redaction).to_one_line_string(true),
+//!     "BatchScan { table: t1, predicate: <redacted>, columns: [id, <redacted>] }",
+//! );
+//! ```
+
+use std::sync::Arc;
+
+use crate::{Pretty, Str, XmlNode};
+
+/// What a hidden text is replaced with.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum Mask {
+    /// `<redacted>`
+    #[default]
+    Redacted,
+    /// Like `<redacted 9d3a0f1c>`, the same for the same text, so that
+    /// equal values can still be told apart from different ones.
+    Hash,
+}
+
+/// Decides whether a text is hidden.
+pub type ValuePredicate = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// Which texts are hidden: the values of fields, the notes and the metrics.
+/// Names of records and keys of fields are kept.
+#[derive(Clone, Default)]
+pub struct Redaction {
+    /// Keys of the fields whose values are hidden, all the texts in them,
+    /// including the notes and metrics of the records there.
+    /// `*` matches any part of a key and `?` any character.
+    pub keys: Vec<String>,
+    /// Texts for which this is true are hidden wherever they are.
+    pub predicate: Option<ValuePredicate>,
+    pub mask: Mask,
+}
+
+/// Matches `key` against a pattern with `*` and `?`.
+fn glob(pattern: &[char], key: &[char]) -> bool {
+    match (pattern.split_first(), key.split_first()) {
+        (None, None) => true,
+        (Some(('*', p)), _) => glob(p, key) || (!key.is_empty() && glob(pattern, &key[1..])),
+        (Some(('?', p)), Some((_, k))) => glob(p, k),
+        (Some((a, p)), Some((b, k))) => a == b && glob(p, k),
+        _ => false,
+    }
+}
+
+/// 64-bit FNV-1a, which does not change between runs and versions.
+fn fnv1a(s: &str) -> u64 {
+    (s.bytes()).fold(0xcbf2_9ce4_8422_2325, |h, b| {
+        (h ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+impl Redaction {
+    pub fn is_noop(&self) -> bool {
+        self.keys.is_empty() && self.predicate.is_none()
+    }
+
+    pub fn with_predicate(self, predicate: impl Fn(&str) -> bool + Send + Sync + 'static) -> Self {
+        Self {
+            predicate: Some(Arc::new(predicate)),
+            ..self
+        }
+    }
+
+    fn hides_key(&self, key: &str) -> bool {
+        let key: Vec<_> = key.chars().collect();
+        (self.keys.iter()).any(|p| glob(&p.chars().collect::<Vec<_>>(), &key))
+    }
+
+    /// `hidden` is set inside a field whose key is hidden.
+    fn hides(&self, text: &str, hidden: bool) -> bool {
+        hidden || self.predicate.as_ref().is_some_and(|p| p(text))
+    }
+
+    fn mask<'a>(&self, text: &str) -> Str<'a> {
+        match self.mask {
+            Mask::Redacted => "<redacted>".into(),
+            Mask::Hash => format!("<redacted {:08x}>", fnv1a(text) as u32).into(),
+        }
+    }
+
+    fn text<'a>(&self, text: &Str<'a>, hidden: bool) -> Str<'a> {
+        if self.hides(text, hidden) {
+            self.mask(text)
+        } else {
+            text.clone()
+        }
+    }
+
+    fn pretty<'a>(&self, pretty: &Pretty<'a>, hidden: bool) -> Pretty<'a> {
+        use Pretty::*;
+        match pretty {
+            Text(s) => Text(self.text(s, hidden)),
+            Scalar(s) if self.hides(&s.raw(), hidden) => Text(self.mask(&s.raw())),
+            Record(xml) => Record(self.xml(xml, hidden)),
+            Array(v) => Array(v.iter().map(|p| self.pretty(p, hidden)).collect()),
+            Seq(s) => Seq(s.map_items(|p| self.pretty(p, hidden))),
+            Map(m) => Map((m.iter())
+                .map(|(k, v)| (k.clone(), self.pretty(v, hidden || self.hides_key(k))))
+                .collect()),
+            Table(t) => {
+                let columns: Vec<_> = (t.columns.iter())
+                    .map(|c| hidden || self.hides_key(c))
+                    .collect();
+                let rows = (t.rows.iter())
+                    .map(|row| {
+                        (row.iter().zip(&columns))
+                            .map(|(p, &hidden)| self.pretty(p, hidden))
+                            .collect()
+                    })
+                    .collect();
+                Table(crate::Table {
+                    columns: t.columns.clone(),
+                    rows,
+                })
+            }
+            Scalar(..) | Elided(..) | Linearized(..) | Ref(..) => pretty.clone(),
+        }
+    }
+
+    fn xml<'a>(&self, xml: &XmlNode<'a>, hidden: bool) -> XmlNode<'a> {
+        let fields = (xml.fields.iter())
+            .map(|(k, v)| {
+                let hidden = hidden || self.hides_key(k);
+                (k.clone(), self.pretty(v, hidden))
+            })
+            .collect();
+        let children = (xml.children.iter())
+            .map(|c| self.pretty(c, hidden))
+            .collect();
+        XmlNode {
+            notes: xml.notes.iter().map(|n| self.text(n, hidden)).collect(),
+            metrics: xml.metrics.iter().map(|m| self.text(m, hidden)).collect(),
+            ..xml.rebuild(fields, children)
+        }
+    }
+}
+
+impl<'a> Pretty<'a> {
+    /// Replaces the texts that `redaction` hides, see the [module docs](crate::redact).
+    /// The shape of the tree is kept.
+    pub fn redact(&self, redaction: &Redaction) -> Self {
+        redaction.pretty(self, false)
+    }
+}
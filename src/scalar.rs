@@ -0,0 +1,127 @@
+//! Leaves that know their type, unlike [`Pretty::Text`].
+//!
+//! ```rust
+//! use pretty_xmlish::Pretty;
+//! let scan = Pretty::record("BatchScan")
+//!     .field("table", Pretty::ident("t1"))
+//!     .field("limit", Pretty::int(10))
+//!     .field("predicate", Pretty::string("it's"))
+//!     .field("exact", Pretty::bool(false))
+//!     .build();
+//! assert_eq!(
+//!     scan.to_one_line_string(true),
+//!     "BatchScan { table: t1, limit: 10, predicate: 'it''s', exact: false }",
+//! );
+//! ```
+
+use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
+
+use crate::{Pretty, Str};
+
+#[derive(Clone, Debug)]
+pub enum Scalar<'a> {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Null,
+    /// A name, like a column or a table, printed as is.
+    Ident(Str<'a>),
+    /// A string literal, printed in single quotes.
+    Str(Str<'a>),
+}
+
+impl PartialEq for Scalar<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        use Scalar::*;
+        match (self, other) {
+            (Int(a), Int(b)) => a == b,
+            // Bitwise, so that `Eq` holds for NaN
+            (Float(a), Float(b)) => a.to_bits() == b.to_bits(),
+            (Bool(a), Bool(b)) => a == b,
+            (Null, Null) => true,
+            (Ident(a), Ident(b)) | (Str(a), Str(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Scalar<'_> {}
+
+impl Hash for Scalar<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        use Scalar::*;
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Int(i) => i.hash(state),
+            Float(f) => f.to_bits().hash(state),
+            Bool(b) => b.hash(state),
+            Null => {}
+            Ident(s) | Str(s) => s.hash(state),
+        }
+    }
+}
+
+/// How the tree renderers print it.
+impl Display for Scalar<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        use Scalar::*;
+        match self {
+            Int(i) => write!(f, "{}", i),
+            // `1.0` rather than `1`
+            Float(x) => write!(f, "{:?}", x),
+            Bool(b) => write!(f, "{}", b),
+            Null => f.write_str("null"),
+            Ident(s) => f.write_str(s),
+            Str(s) => write!(f, "'{}'", s.replace('\'', "''")),
+        }
+    }
+}
+
+impl Scalar<'_> {
+    /// Without the quotes of string literals, for formats
+    /// that have their own quoting, like XML attributes.
+    pub fn raw(&self) -> String {
+        match self {
+            Scalar::Str(s) => s.to_string(),
+            _ => self.to_string(),
+        }
+    }
+
+    pub fn is_number(&self) -> bool {
+        matches!(self, Scalar::Int(..) | Scalar::Float(..))
+    }
+}
+
+impl<'a> From<Scalar<'a>> for Pretty<'a> {
+    fn from(scalar: Scalar<'a>) -> Self {
+        Pretty::Scalar(scalar)
+    }
+}
+
+/// Numbers cannot be turned `into` a [`Pretty`], since strings already are.
+impl<'a> Pretty<'a> {
+    pub fn int(i: i64) -> Self {
+        Self::Scalar(Scalar::Int(i))
+    }
+
+    pub fn float(x: f64) -> Self {
+        Self::Scalar(Scalar::Float(x))
+    }
+
+    pub fn bool(b: bool) -> Self {
+        Self::Scalar(Scalar::Bool(b))
+    }
+
+    pub fn null() -> Self {
+        Self::Scalar(Scalar::Null)
+    }
+
+    pub fn ident(s: impl Into<Str<'a>>) -> Self {
+        Self::Scalar(Scalar::Ident(s.into()))
+    }
+
+    pub fn string(s: impl Into<Str<'a>>) -> Self {
+        Self::Scalar(Scalar::Str(s.into()))
+    }
+}
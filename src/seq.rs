@@ -0,0 +1,111 @@
+//! Sequences with their own delimiters, like tuples or conjunctions.
+//!
+//! ```rust
+//! use pretty_xmlish::{Pretty, Seq};
+//! let filter = Pretty::record("Filter")
+//!     .field("predicate", Seq::separated(" AND ", vec!["$1 > 0".into(), "$2 IS NULL".into()]))
+//!     .field("key", Pretty::tuple(vec![Pretty::int(1), Pretty::int(2)]))
+//!     .build();
+//! assert_eq!(
+//!     filter.to_one_line_string(false),
+//!     "Filter { predicate: $1 > 0 AND $2 IS NULL, key: (1, 2) }",
+//! );
+//! ```
+//!
+//! When broken into lines, every item but the last is followed
+//! by the separator without its trailing spaces.
+//! The opening delimiter ends the first line and the closing one
+//! has a line of its own, in both layouts.
+
+use crate::{display_width, Pretty, Str};
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Seq<'a> {
+    pub open: Str<'a>,
+    pub sep: Str<'a>,
+    pub close: Str<'a>,
+    pub items: Vec<Pretty<'a>>,
+}
+
+impl<'a> Seq<'a> {
+    pub fn new(
+        open: impl Into<Str<'a>>,
+        sep: impl Into<Str<'a>>,
+        close: impl Into<Str<'a>>,
+        items: Vec<Pretty<'a>>,
+    ) -> Self {
+        Self {
+            open: open.into(),
+            sep: sep.into(),
+            close: close.into(),
+            items,
+        }
+    }
+
+    /// `(a, b)`
+    pub fn tuple(items: Vec<Pretty<'a>>) -> Self {
+        Self::new("(", ", ", ")", items)
+    }
+
+    /// `{a, b}`
+    pub fn set(items: Vec<Pretty<'a>>) -> Self {
+        Self::new("{", ", ", "}", items)
+    }
+
+    /// `a AND b` for a separator of ` AND `, without delimiters.
+    pub fn separated(sep: impl Into<Str<'a>>, items: Vec<Pretty<'a>>) -> Self {
+        Self::new("", sep, "", items)
+    }
+
+    /// Same delimiters, `f` applied to every item.
+    pub(crate) fn map_items<'t>(&'t self, f: impl FnMut(&'t Pretty<'a>) -> Pretty<'a>) -> Self {
+        Self {
+            items: self.items.iter().map(f).collect(),
+            ..self.with_items(vec![])
+        }
+    }
+
+    pub(crate) fn with_items(&self, items: Vec<Pretty<'a>>) -> Self {
+        Self {
+            open: self.open.clone(),
+            sep: self.sep.clone(),
+            close: self.close.clone(),
+            items,
+        }
+    }
+
+    /// What follows the items but the last when broken into lines.
+    pub(crate) fn line_sep(&self) -> &str {
+        self.sep.trim_end()
+    }
+
+    pub(crate) fn ol_build_str_ascii(&self, reduced_ws: bool, builder: &mut String) {
+        builder.push_str(&self.open);
+        for (i, p) in self.items.iter().enumerate() {
+            if i > 0 {
+                builder.push_str(&self.sep);
+            }
+            p.ol_build_str_ascii(reduced_ws, builder);
+        }
+        builder.push_str(&self.close);
+    }
+
+    pub(crate) fn ol_len(&self, reduced_ws: bool) -> usize {
+        let mem: usize = self.items.iter().map(|p| p.ol_len(reduced_ws)).sum();
+        let mid = self.items.len().saturating_sub(1) * display_width(&self.sep);
+        mem + mid + display_width(&self.open) + display_width(&self.close)
+    }
+}
+
+impl<'a> From<Seq<'a>> for Pretty<'a> {
+    fn from(seq: Seq<'a>) -> Self {
+        Pretty::Seq(seq)
+    }
+}
+
+impl<'a> Pretty<'a> {
+    /// See [`Seq::tuple`].
+    pub fn tuple(items: Vec<Self>) -> Self {
+        Self::Seq(Seq::tuple(items))
+    }
+}
@@ -0,0 +1,187 @@
+use crate::{
+    diff::{lcs_align, Diff},
+    display_width,
+    note::NOTE_MARK,
+    parse_unicode::split_field,
+    LinedBuffer, Pretty, PrettyConfig, Str,
+};
+
+const GUTTER: &str = " │ ";
+
+/// Where a line is in the tree, from the root line.
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Step<'s> {
+    Field(&'s str),
+    /// Children of a record and items of an array or a sequence, not
+    /// counting the elided ones.
+    Child(usize),
+    /// Any other line.
+    Line(usize),
+}
+
+/// The path of each line. Notes, elided entries, closing delimiters and
+/// the lines of tables are not nodes of their own, and are numbered with
+/// [`Step::Line`] below the node they belong to.
+fn paths<'s>(lines: &[&'s str]) -> Vec<Vec<Step<'s>>> {
+    // Prefix width, path, children and other lines so far, from the top
+    // of the tree, which has no line if it is an array.
+    let mut stack: Vec<(Option<usize>, Vec<Step>, usize, usize)> = vec![(None, vec![], 0, 0)];
+    let mut paths = Vec::with_capacity(lines.len());
+    for line in lines {
+        let content = line.trim_start_matches(|c| "│├└┌─ ".contains(c)).trim_end();
+        let depth = display_width(&line[..line.len() - content.len()]);
+        let is_node = !(content.is_empty()
+            || content.starts_with(NOTE_MARK)
+            || content.starts_with("… ")
+            || content.ends_with(['│', '┐', '┤', '┘'])
+            || content.chars().all(|c| ")]}>".contains(c)));
+        while stack
+            .last()
+            .is_some_and(|(d, ..)| d.is_some_and(|d| d >= depth))
+        {
+            stack.pop();
+        }
+        let (parent_depth, parent, children, others) = stack.last_mut().unwrap();
+        let step = match split_field(content) {
+            _ if !is_node => {
+                *others += 1;
+                Step::Line(*others - 1)
+            }
+            Some((k, _)) => Step::Field(k),
+            None => {
+                *children += 1;
+                Step::Child(*children - 1)
+            }
+        };
+        let path = match step {
+            // The root
+            Step::Child(0) if parent_depth.is_none() && depth == 0 => vec![],
+            step => [&parent[..], &[step]].concat(),
+        };
+        if is_node {
+            stack.push((Some(depth), path.clone(), 0, 0));
+        }
+        paths.push(path);
+    }
+    paths
+}
+
+/// Whether the old and the new side of `diff` have lines of their own.
+fn shown(diff: &Diff) -> (bool, bool) {
+    let shown = |p: &Pretty| !p.is_elided();
+    match diff {
+        Diff::Same(p) => (shown(p), shown(p)),
+        Diff::Added(p) => (false, shown(p)),
+        Diff::Removed(p) => (shown(p), false),
+        Diff::Changed(old, new) => (shown(old), shown(new)),
+        Diff::Record(_) | Diff::Array(_) | Diff::Seq(..) | Diff::Map(_) => (true, true),
+    }
+}
+
+/// Where the line at `path` on the old side of `diff` is on the new side,
+/// if it is there.
+fn translate<'s>(diff: &Diff, path: &[Step<'s>]) -> Option<Vec<Step<'s>>> {
+    let fields = |fields: &[(Str, Diff)], k: &'s str, rest| {
+        let (_, d) = fields.iter().find(|(key, _)| key == k)?;
+        Some([vec![Step::Field(k)], translate(d, rest)?].concat())
+    };
+    let items = |items: &[Diff], i: usize, rest| {
+        let (mut old, mut new) = (0, 0);
+        for d in items {
+            let (is_old, is_new) = shown(d);
+            if is_old && old == i {
+                return Some([vec![Step::Child(new)], translate(d, rest)?].concat());
+            }
+            old += is_old as usize;
+            new += is_new as usize;
+        }
+        None
+    };
+    match (diff, path) {
+        (Diff::Same(_), _) => Some(path.to_vec()),
+        (Diff::Added(_) | Diff::Removed(_), _) => None,
+        (_, []) => Some(vec![]),
+        (_, [Step::Line(i)]) => Some(vec![Step::Line(*i)]),
+        (Diff::Record(r), [Step::Field(k), rest @ ..]) => fields(&r.fields, k, rest),
+        (Diff::Map(m), [Step::Field(k), rest @ ..]) => fields(m, k, rest),
+        (Diff::Record(r), [Step::Child(i), rest @ ..]) => items(&r.children, *i, rest),
+        (Diff::Array(v) | Diff::Seq(_, v), [Step::Child(i), rest @ ..]) => items(v, *i, rest),
+        _ => None,
+    }
+}
+
+/// Rows of the output: the lines of the nodes matched by `diff` are on
+/// the same row, and the others have a blank opposite them.
+fn rows<'s>(
+    diff: &Diff,
+    left: &[&'s str],
+    right: &[&'s str],
+) -> Vec<(Option<&'s str>, Option<&'s str>)> {
+    let l_paths: Vec<_> = (paths(left).iter()).map(|p| translate(diff, p)).collect();
+    let r_paths: Vec<_> = paths(right).into_iter().map(Some).collect();
+    (lcs_align(&l_paths, &r_paths, |l, r| l.is_some() && l == r).into_iter())
+        .map(|(l, r)| (l.map(|l| left[l]), r.map(|r| right[r])))
+        .collect()
+}
+
+impl PrettyConfig {
+    /// Renders two trees in adjacent columns, each laid out to half of
+    /// the width, like "before" and "after" an optimization. The nodes
+    /// matched by [`Pretty::diff`] are on the same rows.
+    /// Returns the width of the output, exclusive of the boundaries.
+    pub fn unicode_side_by_side(
+        &mut self,
+        out: &mut String,
+        left: &Pretty,
+        right: &Pretty,
+    ) -> usize {
+        let mut half = PrettyConfig {
+            need_boundaries: false,
+            width: self.width.saturating_sub(display_width(GUTTER)) / 2,
+            ..self.clone()
+        };
+        let diff = half.preprocess(left).diff(&half.preprocess(right));
+        let (mut l_out, mut r_out) = (String::new(), String::new());
+        let l_width = half.clone().unicode(&mut l_out, left);
+        let r_width = half.unicode(&mut r_out, right);
+        let (l_lines, r_lines): (Vec<_>, Vec<_>) =
+            (l_out.lines().collect(), r_out.lines().collect());
+        // Lines that cannot be broken are wider than the half, the box grows
+        let widest = |lines: &[&str]| lines.iter().map(|l| display_width(l)).max();
+        let l_width = widest(&l_lines).map_or(l_width, |w| w.max(l_width));
+        let r_width = widest(&r_lines).map_or(r_width, |w| w.max(r_width));
+        let width = l_width + display_width(GUTTER) + r_width;
+
+        let mut dat = LinedBuffer {
+            out,
+            width,
+            config: self,
+            already_occupied: 0,
+            metric_columns: vec![],
+            pending_metrics: None,
+        };
+        self.horizon(dat.out, width);
+        if self.need_boundaries {
+            dat.out.push('\n');
+        }
+        let rows = rows(&diff, &l_lines, &r_lines);
+        for (i, (l, r)) in rows.iter().enumerate() {
+            dat.begin_line();
+            let l = l.unwrap_or_default();
+            dat.push(l);
+            dat.pip(l_width - display_width(l));
+            match r {
+                Some(r) => {
+                    dat.push(GUTTER);
+                    dat.push(r);
+                }
+                None => dat.push(GUTTER.trim_end()),
+            }
+            if i < rows.len() - 1 || self.need_boundaries {
+                dat.pusheen();
+            }
+        }
+        self.horizon(dat.out, width);
+        width
+    }
+}
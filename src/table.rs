@@ -0,0 +1,219 @@
+use std::iter::repeat_n;
+
+use crate::{display_width, Pretty, Str, XmlNode};
+
+/// Rows of values under named columns, printed as a grid.
+/// Cells are always printed on one line.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Table<'a> {
+    pub columns: Vec<Str<'a>>,
+    pub rows: Vec<Vec<Pretty<'a>>>,
+}
+
+/// Box-drawing characters of a grid: horizontal, vertical,
+/// then the left, middle and right junctions of the top, middle
+/// and bottom borders.
+struct Borders {
+    h: char,
+    v: char,
+    top: [char; 3],
+    mid: [char; 3],
+    bottom: [char; 3],
+}
+
+const UNICODE: Borders = Borders {
+    h: '\u{2500}',
+    v: '\u{2502}',
+    top: ['\u{250C}', '\u{252C}', '\u{2510}'],
+    mid: ['\u{251C}', '\u{253C}', '\u{2524}'],
+    bottom: ['\u{2514}', '\u{2534}', '\u{2518}'],
+};
+
+const ASCII: Borders = Borders {
+    h: '-',
+    v: '|',
+    top: ['+'; 3],
+    mid: ['+'; 3],
+    bottom: ['+'; 3],
+};
+
+fn is_number(cell: &Pretty) -> bool {
+    match cell {
+        Pretty::Scalar(s) => s.is_number(),
+        Pretty::Text(s) => s.parse::<f64>().is_ok(),
+        _ => false,
+    }
+}
+
+impl<'a> Table<'a> {
+    /// An array of at least two records that have no children and the same
+    /// field keys in the same order. The names of the records are dropped,
+    /// and the elided elements are rows of their own, like in [`Pretty::elide`].
+    pub fn from_records(list: &[Pretty<'a>]) -> Option<Self> {
+        let records: Vec<_> = (list.iter())
+            .filter_map(|p| match p {
+                Pretty::Record(xml) if !xml.has_children() => Some(Some(xml)),
+                Pretty::Elided(_) => None,
+                _ => Some(None),
+            })
+            .collect::<Option<_>>()?;
+        let (first, rest) = records.split_first()?;
+        let columns: Vec<_> = first.fields.iter().map(|(k, _)| k.clone()).collect();
+        let same_keys = |xml: &&XmlNode<'a>| {
+            let keys = (xml.fields.iter()).map(|(k, _)| k);
+            keys.eq(columns.iter()) && !xml.fields.values().any(Pretty::is_elided)
+        };
+        if rest.is_empty() || columns.is_empty() || !records.iter().all(same_keys) {
+            return None;
+        }
+        let rows = (list.iter())
+            .map(|p| match p {
+                Pretty::Record(xml) => xml.fields.iter().map(|(_, v)| v.clone()).collect(),
+                elided => vec![elided.clone()],
+            })
+            .collect();
+        Some(Self { columns, rows })
+    }
+
+    /// Same columns, `f` applied to every cell.
+    pub(crate) fn map_cells<'t>(&'t self, mut f: impl FnMut(&'t Pretty<'a>) -> Pretty<'a>) -> Self {
+        let rows = (self.rows.iter())
+            .map(|row| row.iter().map(&mut f).collect())
+            .collect();
+        Self {
+            columns: self.columns.clone(),
+            rows,
+        }
+    }
+
+    fn cells(&self, reduced_ws: bool) -> Vec<Vec<String>> {
+        (self.rows.iter())
+            .map(|row| {
+                row.iter()
+                    .map(|v| v.to_one_line_string(reduced_ws))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn column_widths(&self, cells: &[Vec<String>]) -> Vec<usize> {
+        (self.columns.iter().enumerate())
+            .map(|(i, c)| {
+                let cells = cells.iter().filter_map(|row| row.get(i));
+                (cells.map(|s| display_width(s)))
+                    .chain(Some(display_width(c)))
+                    .max()
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    /// Width of the grid, borders included.
+    pub(crate) fn width(&self, reduced_ws: bool) -> usize {
+        let widths = self.column_widths(&self.cells(reduced_ws));
+        widths.iter().sum::<usize>() + widths.len() * " | ".len() + 1
+    }
+
+    pub(crate) fn grid(&self, reduced_ws: bool, unicode: bool) -> Vec<String> {
+        let b = if unicode { &UNICODE } else { &ASCII };
+        let cells = self.cells(reduced_ws);
+        let widths = self.column_widths(&cells);
+        let border = |[l, m, r]: [char; 3]| {
+            let mut line = String::new();
+            line.push(l);
+            for (i, w) in widths.iter().enumerate() {
+                if i > 0 {
+                    line.push(m);
+                }
+                line.extend(repeat_n(b.h, w + 2));
+            }
+            line.push(r);
+            line
+        };
+        // Numbers are aligned to the right
+        let row = |cells: &mut dyn Iterator<Item = (&str, bool)>| {
+            let mut line = String::new();
+            line.push(b.v);
+            for ((s, right), w) in cells.zip(&widths) {
+                let pad = w - display_width(s);
+                line.push(' ');
+                if right {
+                    line.extend(repeat_n(' ', pad));
+                    line.push_str(s);
+                } else {
+                    line.push_str(s);
+                    line.extend(repeat_n(' ', pad));
+                }
+                line.push(' ');
+                line.push(b.v);
+            }
+            line
+        };
+        let mut lines = Vec::with_capacity(self.rows.len() + 4);
+        lines.push(border(b.top));
+        lines.push(row(&mut self.columns.iter().map(|c| (c.as_ref(), false))));
+        lines.push(border(b.mid));
+        for (cells, values) in cells.iter().zip(&self.rows) {
+            let empty = repeat_n(("", false), self.columns.len().saturating_sub(cells.len()));
+            let numbers = values.iter().map(is_number);
+            lines.push(row(&mut cells
+                .iter()
+                .map(String::as_str)
+                .zip(numbers)
+                .chain(empty)));
+        }
+        lines.push(border(b.bottom));
+        lines
+    }
+
+    /// Like an array of records without names: `[ { a: 1 }, { a: 2 } ]`.
+    pub(crate) fn ol_build_str_ascii(&self, reduced_ws: bool, builder: &mut String) {
+        if self.rows.is_empty() {
+            builder.push_str("[]");
+            return;
+        }
+        builder.push('[');
+        if !reduced_ws {
+            builder.push(' ');
+        }
+        for (i, row) in self.rows.iter().enumerate() {
+            if i > 0 {
+                builder.push_str(", ");
+            }
+            if let [elided @ Pretty::Elided(..)] = &row[..] {
+                elided.ol_build_str_ascii(reduced_ws, builder);
+                continue;
+            }
+            builder.push_str("{ ");
+            for (j, (k, v)) in self.columns.iter().zip(row).enumerate() {
+                if j > 0 {
+                    builder.push_str(", ");
+                }
+                builder.push_str(k);
+                builder.push_str(": ");
+                v.ol_build_str_ascii(reduced_ws, builder);
+            }
+            builder.push_str(" }");
+        }
+        if !reduced_ws {
+            builder.push(' ');
+        }
+        builder.push(']');
+    }
+
+    pub(crate) fn ol_len(&self, reduced_ws: bool) -> usize {
+        let mut builder = String::new();
+        self.ol_build_str_ascii(reduced_ws, &mut builder);
+        display_width(&builder)
+    }
+}
+
+impl<'a> Pretty<'a> {
+    /// The table that this array looks like, see [`Table::from_records`].
+    pub fn to_table(&self) -> Option<Table<'a>> {
+        match self {
+            Pretty::Array(v) => Table::from_records(v),
+            _ => None,
+        }
+    }
+}
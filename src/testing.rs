@@ -0,0 +1,119 @@
+//! Snapshot tests for rendered trees, behind the `testing` feature.
+//!
+//! ```rust,ignore
+//! use pretty_xmlish::{assert_pretty_snapshot, Pretty};
+//! let scan = Pretty::childless_record("BatchScan", vec![("table", "t1".into())]);
+//! // Compared with `snapshots/batch_scan.txt` next to `Cargo.toml`
+//! assert_pretty_snapshot!("batch_scan", scan);
+//! ```
+//!
+//! A missing or outdated snapshot fails the test. Run the tests with the
+//! environment variable [`BLESS`] set to write the current output instead.
+
+use std::fs;
+use std::path::Path;
+
+use crate::{diff::lcs_align, Pretty, PrettyConfig};
+
+/// Set this environment variable to overwrite the snapshots.
+pub const BLESS: &str = "PRETTY_XMLISH_BLESS";
+
+/// The configuration of [`assert_pretty_snapshot!`] when none is given.
+/// It does not depend on the terminal.
+pub fn snapshot_config() -> PrettyConfig {
+    PrettyConfig {
+        width: 80,
+        need_boundaries: false,
+        colors: false,
+        ..Default::default()
+    }
+}
+
+/// Every line of both texts, prefixed by ` `, `-` or `+`.
+fn unified_diff(old: &str, new: &str) -> String {
+    let (old, new): (Vec<_>, Vec<_>) = (old.lines().collect(), new.lines().collect());
+    let mut out = String::new();
+    for pair in lcs_align(&old, &new, |a, b| a == b) {
+        let (sign, line) = match pair {
+            (Some(i), Some(_)) => (' ', old[i]),
+            (Some(i), None) => ('-', old[i]),
+            (None, Some(j)) => ('+', new[j]),
+            (None, None) => continue,
+        };
+        out.push(sign);
+        out.push(' ');
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+/// The difference of the trees if both texts can be read back,
+/// otherwise, or if only the layout changed, the difference of the lines.
+/// Both sides are read back, so that scalars compare as they are printed.
+fn explain(old: &str, new: &str, mut config: PrettyConfig) -> String {
+    match (Pretty::parse_unicode(old), Pretty::parse_unicode(new)) {
+        (Ok(old_tree), Ok(new_tree)) if old_tree != new_tree => {
+            let mut out = String::new();
+            config.unicode_diff(&mut out, &old_tree, &new_tree);
+            out
+        }
+        _ => unified_diff(old, new),
+    }
+}
+
+/// Renders `pretty` in the unicode style and compares it with
+/// `dir/name.txt`. Prefer [`assert_pretty_snapshot!`].
+#[track_caller]
+pub fn assert_snapshot(dir: &Path, name: &str, pretty: &Pretty, config: PrettyConfig) {
+    let mut new = String::new();
+    // Rendering narrows the width of the config, the diff needs the original
+    config.clone().unicode(&mut new, pretty);
+    if !new.ends_with('\n') {
+        new.push('\n');
+    }
+    let path = dir.join(format!("{}.txt", name));
+    let old = fs::read_to_string(&path).ok();
+    if old.as_deref() == Some(new.as_str()) {
+        return;
+    }
+    if std::env::var_os(BLESS).is_some() {
+        fs::create_dir_all(dir)
+            .and_then(|_| fs::write(&path, &new))
+            .unwrap_or_else(|e| panic!("cannot write {}: {}", path.display(), e));
+        return;
+    }
+    match old {
+        None => panic!(
+            "missing snapshot {}, run with {}=1 to create it:\n{}",
+            path.display(),
+            BLESS,
+            new
+        ),
+        Some(old) => panic!(
+            "snapshot {} does not match, run with {}=1 to update it:\n{}",
+            path.display(),
+            BLESS,
+            explain(&old, &new, config)
+        ),
+    }
+}
+
+/// `assert_pretty_snapshot!(name, pretty)` compares the rendering of `pretty`
+/// with `snapshots/<name>.txt` in the directory of the calling crate,
+/// using [`snapshot_config`](crate::testing::snapshot_config) unless a
+/// [`PrettyConfig`] is given as the third argument.
+#[macro_export]
+macro_rules! assert_pretty_snapshot {
+    ($name:expr, $pretty:expr $(,)?) => {
+        $crate::assert_pretty_snapshot!($name, $pretty, $crate::testing::snapshot_config())
+    };
+    ($name:expr, $pretty:expr, $config:expr $(,)?) => {
+        $crate::testing::assert_snapshot(
+            &::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("snapshots"),
+            $name,
+            &$pretty,
+            $config,
+        )
+    };
+}
@@ -1,6 +1,9 @@
-use std::iter::repeat;
+use std::iter::repeat_n;
 
-use crate::{LinedBuffer, Pretty, PrettyConfig, XmlNode};
+use crate::{
+    display_width, elision::ellipsis, key_len, metrics::metrics_width, ref_str, LinedBuffer,
+    Pretty, PrettyConfig, XmlNode,
+};
 
 /// https://www.w3.org/TR/xml-entity-names/025.html
 /// These unicode characters are assumed to have length 1!
@@ -14,25 +17,34 @@ mod characters {
 
 impl PrettyConfig {
     pub fn unicode(&mut self, out: &mut String, pretty: &Pretty) -> usize {
-        let (pretty, width) = self.interesting_unicode(0, pretty, 0);
+        let pretty = self.preprocess(pretty);
+        let reserved = self.reserved_metrics_width(&pretty);
+        self.width = self.width.saturating_sub(reserved);
+        let (pretty, width) = self.interesting_unicode(0, &pretty, 0);
         self.width = width;
         // The second time folds previous lines that can be wrapped with the extended width.
         let (pretty, width) = self.interesting_unicode(0, &pretty, 0);
+        let metric_columns = self.metric_columns(&pretty, true);
+        let width = width + metrics_width(&metric_columns);
         let mut dat = LinedBuffer {
             out,
             width,
             config: self,
             already_occupied: 0,
+            metric_columns,
+            pending_metrics: None,
         };
         self.horizon(dat.out, width);
         if self.need_boundaries {
-            dat.out.push_str("\n");
+            dat.out.push('\n');
         }
 
         dat.begin_line();
         dat.line_unicode(&pretty, 0, Default::default(), "");
         if self.need_boundaries {
             dat.pusheen();
+        } else {
+            dat.push_metrics();
         }
 
         self.horizon(dat.out, width);
@@ -52,30 +64,78 @@ impl PrettyConfig {
             return (Pretty::Linearized(pretty, ol_len), len);
         }
         let next_indent = base_indent + self.indent;
+        // In place of a child or an element, the grid starts on the first line
+        let table_len = |t: &crate::Table| {
+            let indent = if additional == 0 {
+                base_indent
+            } else {
+                next_indent
+            };
+            (indent + t.width(self.reduced_spaces)).max(first_line_base)
+        };
         use Pretty::*;
         match pretty {
             Text(s) => {
-                let len = s.chars().count() + first_line_base;
+                let len = display_width(s) + first_line_base;
                 (s.as_ref().into(), len)
             }
+            Scalar(s) => {
+                let len = display_width(&s.to_string()) + first_line_base;
+                (pretty.clone(), len)
+            }
             Array(v) => {
+                if let Some(t) = (self.tables).then(|| pretty.to_table()).flatten() {
+                    let len = table_len(&t);
+                    return (Table(t), len);
+                }
                 if v.len() == 1 {
                     return self.interesting_unicode(base_indent, &v[0], additional);
                 }
                 let (v, lens): (Vec<_>, Vec<_>) = (v.iter())
                     .map(|p| self.interesting_unicode(next_indent, p, 0))
                     .unzip();
-                let max = (lens.into_iter().max()).unwrap_or(first_line_base + "[]".len());
+                // The key stays on the first line
+                let max = (lens.into_iter().max())
+                    .map_or(first_line_base + "[]".len(), |max| max.max(first_line_base));
                 (Array(v), max)
             }
+            Seq(s) => {
+                let sep = display_width(s.line_sep());
+                let mut lens = vec![];
+                let s = s.map_items(|p| {
+                    let (p, len) = self.interesting_unicode(next_indent, p, 0);
+                    lens.push(len + sep);
+                    p
+                });
+                let (open, close) = (display_width(&s.open), display_width(&s.close));
+                let delimiters = match s.items.is_empty() {
+                    true => vec![first_line_base + open + close],
+                    false => vec![first_line_base + open, base_indent + close],
+                };
+                let max = (lens.into_iter().chain(delimiters).max()).unwrap();
+                (Seq(s), max)
+            }
+            Map(m) => {
+                let (fields, lens): (Vec<_>, Vec<_>) = (m.iter())
+                    .map(|(k, v)| {
+                        let (f, len) = self.interesting_unicode(next_indent, v, key_len(k, v));
+                        ((k.clone(), f), len)
+                    })
+                    .unzip();
+                // The key stays on the first line
+                let max = (lens.into_iter().max())
+                    .map_or(first_line_base + "{}".len(), |max| max.max(first_line_base));
+                (Map(fields.into()), max)
+            }
             Record(xml) => {
-                let header = xml.name.chars().count() + first_line_base;
+                let header = display_width(&xml.header()) + first_line_base;
                 // Here, `len` does not include the children
                 let fields_is_linear = xml.fields_is_linear
                     || (len < self.width && xml.fields.iter().all(|(_, t)| !t.has_children()));
                 let (fields, f_lens): (Vec<_>, Vec<_>) = (xml.fields.iter())
-                    .map(|(k, v)| {
-                        let additional = k.chars().count() + ": ".len();
+                    .zip(self.key_pads(xml))
+                    .map(|((k, v), pad)| {
+                        let additional = key_len(k, v) + pad;
                         let (f, len) = self.interesting_unicode(next_indent, v, additional);
                         ((k.clone(), f), len)
                     })
@@ -89,18 +149,26 @@ impl PrettyConfig {
                     } else {
                         f_lens.into_iter()
                     })
-                    .chain(Some(header).into_iter())
+                    .chain(Some(header))
+                    .chain(xml.note_lines().map(|n| next_indent + display_width(&n)))
                     .max()
                     .unwrap();
                 let xml_node = XmlNode {
-                    name: xml.name.clone(),
-                    fields,
                     fields_is_linear,
-                    children,
+                    ..xml.rebuild(fields.into(), children)
                 };
                 (Record(xml_node), max)
             }
             Linearized(_, l) => (pretty.clone(), *l),
+            Table(t) => (pretty.clone(), table_len(t)),
+            Ref(id) => (
+                pretty.clone(),
+                display_width(&ref_str(*id)) + first_line_base,
+            ),
+            Elided(n) => (
+                pretty.clone(),
+                display_width(&ellipsis(*n)) + first_line_base,
+            ),
         }
     }
 
@@ -112,7 +180,7 @@ impl PrettyConfig {
             editor.push(start);
             remaining -= 1;
         }
-        editor.extend(repeat(fill).take(remaining - 1));
+        editor.extend(repeat_n(fill, remaining - 1));
         if !self.reduced_spaces {
             editor.push(' ');
         }
@@ -132,7 +200,13 @@ impl<'a> LinedBuffer<'a> {
         let indent_len = indent_len + self.config.indent;
 
         enum Cubical<'a> {
-            Cartesian(&'a [Pretty<'a>]),
+            /// Array elements and elided fields have no key, then what an empty one looks like,
+            /// and the delimiters around the broken items with what follows all but the last
+            Cartesian(
+                Vec<(Option<&'a str>, &'a Pretty<'a>)>,
+                String,
+                (&'a str, &'a str, &'a str),
+            ),
             DeMorgan(&'a XmlNode<'a>),
         }
         use Cubical::*;
@@ -142,14 +216,65 @@ impl<'a> LinedBuffer<'a> {
                 self.push(s);
                 return;
             }
+            Scalar(s) => {
+                self.push(one_line_prefix);
+                self.push(&s.to_string());
+                return;
+            }
             Linearized(p, ol_len) => {
+                self.metrics_of_linearized(p);
                 self.push(one_line_prefix);
                 p.ol_build_str_ascii(self.config.reduced_spaces, self.out);
                 self.already_occupied += ol_len;
                 return;
             }
+            Ref(id) => {
+                self.push(one_line_prefix);
+                self.push(&ref_str(*id));
+                return;
+            }
+            Elided(n) => {
+                self.push(one_line_prefix);
+                self.push(&ellipsis(*n));
+                return;
+            }
+            Table(t) => {
+                // After a key, the grid is below, where the array elements would be.
+                // Otherwise it is in place of the child or the element.
+                let after_key = !one_line_prefix.is_empty();
+                let table_prefix = match after_key {
+                    true => self.config.append_prefix(prefix, ' ', ' '),
+                    false => prefix.to_string(),
+                };
+                let grid = t.grid(self.config.reduced_spaces, true);
+                for (i, line) in grid.iter().enumerate() {
+                    if i > 0 || after_key {
+                        self.pusheen();
+                        self.begin_line();
+                        self.push(&table_prefix);
+                    }
+                    self.push(line);
+                }
+                return;
+            }
             Record(xml) => DeMorgan(xml),
-            Array(list) => Cartesian(list),
+            Array(list) => Cartesian(
+                list.iter().map(|p| (None, p)).collect(),
+                "[]".into(),
+                ("", "", ""),
+            ),
+            Map(m) => Cartesian(
+                m.iter()
+                    .map(|(k, v)| ((!v.is_elided()).then_some(k.as_ref()), v))
+                    .collect(),
+                "{}".into(),
+                ("", "", ""),
+            ),
+            Seq(s) => Cartesian(
+                s.items.iter().map(|p| (None, p)).collect(),
+                format!("{}{}", s.open, s.close),
+                (&s.open, s.line_sep(), &s.close),
+            ),
         };
         use characters::*;
         let cont_prefix = self.config.append_prefix(prefix, UD, ' ');
@@ -164,15 +289,20 @@ impl<'a> LinedBuffer<'a> {
             }
         };
         match regularity {
-            Cartesian(list) => {
+            Cartesian(list, empty, (open, sep, close)) => {
                 if list.is_empty() {
-                    self.push("[]");
+                    self.push(one_line_prefix);
+                    self.push(&empty);
                     return;
                 }
+                if !open.is_empty() {
+                    self.push(one_line_prefix);
+                    self.push(open);
+                }
                 use characters::*;
                 let fst_field_prefix = self.config.append_prefix(prefix, DR, LR);
                 self.pusheen();
-                for (i, p) in list.iter().enumerate() {
+                for (i, &(k, p)) in list.iter().enumerate() {
                     self.begin_line();
                     let is_not_last_line = i < list.len() - 1;
                     let (cont_prefix, fields_prefix) = if i == 0 {
@@ -180,14 +310,32 @@ impl<'a> LinedBuffer<'a> {
                     } else {
                         choose(is_not_last_line)
                     };
-                    self.push(&fields_prefix);
-                    self.line_unicode(p, indent_len, &cont_prefix, "");
+                    self.push(fields_prefix);
+                    let (one_line_prefix, key_len) = match k {
+                        Some(k) => {
+                            self.push(k);
+                            self.push(":");
+                            (" ", key_len(k, p))
+                        }
+                        None => ("", 0),
+                    };
+                    self.line_unicode(p, indent_len + key_len, cont_prefix, one_line_prefix);
                     if is_not_last_line {
+                        self.push(sep);
                         self.pusheen();
                     }
                 }
+                if !close.is_empty() {
+                    self.pusheen();
+                    self.begin_line();
+                    self.push(prefix);
+                    self.push(close);
+                }
+            }
+            DeMorgan(xml) => {
+                self.push(one_line_prefix);
+                self.line_unicode_xml(xml, choose, indent_len);
             }
-            DeMorgan(xml) => self.line_unicode_xml(xml, choose, indent_len),
         }
     }
 
@@ -198,22 +346,40 @@ impl<'a> LinedBuffer<'a> {
         indent_len: usize,
     ) {
         let has_children = xml.has_children();
+        self.metrics_of(xml);
         if xml.fields_is_linear {
             let reduced_ws = self.config.reduced_spaces;
             xml.ol_build_str_ascii(reduced_ws, self.out);
             self.already_occupied += xml.ol_len(reduced_ws);
             self.pusheen();
         } else {
-            self.push(&xml.name);
+            self.push(&xml.header());
             self.pusheen();
-            for (i, (k, v)) in xml.fields.iter().enumerate() {
+            let (cont_prefix, _) = choose(has_children || !xml.fields.is_empty());
+            for note in xml.note_lines() {
+                self.begin_line();
+                self.push(cont_prefix);
+                self.push(&note);
+                self.pusheen();
+            }
+            let pads = self.config.key_pads(xml);
+            for (i, ((k, v), pad)) in xml.fields.iter().zip(pads).enumerate() {
                 self.begin_line();
                 let is_not_last_line = has_children || i < xml.fields.len() - 1;
                 let (cont_prefix, fields_prefix) = choose(is_not_last_line);
-                self.push(&fields_prefix);
-                self.push(k);
-                self.push(":");
-                self.line_unicode(v, indent_len + k.len() + ": ".len(), &cont_prefix, " ");
+                self.push(fields_prefix);
+                let one_line_prefix = if v.is_elided() {
+                    ""
+                } else {
+                    self.push(k);
+                    self.push(":");
+                    if !v.starts_below_key(false) {
+                        self.pip(pad);
+                    }
+                    " "
+                };
+                let indent_len = indent_len + key_len(k, v) + pad;
+                self.line_unicode(v, indent_len, cont_prefix, one_line_prefix);
                 if is_not_last_line {
                     self.pusheen();
                 }
@@ -223,9 +389,9 @@ impl<'a> LinedBuffer<'a> {
             self.begin_line();
             let is_not_last_line = i < xml.children.len() - 1;
             let (cont_prefix, fields_prefix) = choose(is_not_last_line);
-            self.push(&fields_prefix);
+            self.push(fields_prefix);
             // let prefix = if self.config.reduced_spaces { "" } else { " " };
-            self.line_unicode(child, indent_len, &cont_prefix, "");
+            self.line_unicode(child, indent_len, cont_prefix, "");
             if is_not_last_line {
                 self.pusheen();
             }
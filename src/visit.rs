@@ -0,0 +1,257 @@
+//! Generic traversals, so that a pass over a tree does not have to match
+//! every variant of [`Pretty`].
+//!
+//! ```rust
+//! use pretty_xmlish::{visit::Step, Pretty};
+//! let plan = Pretty::simple_record("Join", vec![("type", "Inner".into())], vec![
+//!     Pretty::childless_record("Scan", vec![("table", "t1".into())]),
+//!     Pretty::childless_record("Scan", vec![("table", "t2".into())]),
+//! ]);
+//! let scans = plan.pre_order().filter(|(_, p)| p.name() == Some("Scan")).count();
+//! assert_eq!(scans, 2);
+//! let operators = plan.fold(&mut |p, subs: Vec<usize>| {
+//!     subs.into_iter().sum::<usize>() + matches!(p, Pretty::Record(..)) as usize
+//! });
+//! assert_eq!(operators, 3);
+//! let renamed = plan.map_records(|mut xml| {
+//!     if xml.name == "Scan" {
+//!         xml.name = "TableScan".into();
+//!     }
+//!     xml
+//! });
+//! assert!(renamed.post_order().any(|(path, p)| p.name() == Some("TableScan") && path == [Step::Child(1)]));
+//! ```
+
+use std::mem;
+
+use crate::{Pretty, Str, XmlNode};
+
+/// A step from a tree to one of its sub-trees.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Step<'a> {
+    /// The value of the field with this key.
+    Field(Str<'a>),
+    Child(usize),
+    /// An element of an array.
+    Element(usize),
+    /// A cell of a table, by row and column.
+    Cell(usize, usize),
+}
+
+/// The steps from the root of a traversal.
+pub type Path<'a> = Vec<Step<'a>>;
+
+pub trait Visitor<'a> {
+    /// Called before the sub-trees, which are skipped if this returns false.
+    fn enter(&mut self, _path: &[Step<'a>], _pretty: &Pretty<'a>) -> bool {
+        true
+    }
+
+    /// Called after the sub-trees.
+    fn leave(&mut self, _path: &[Step<'a>], _pretty: &Pretty<'a>) {}
+}
+
+pub trait VisitorMut<'a> {
+    /// Called before the sub-trees, which are skipped if this returns false.
+    fn enter(&mut self, _path: &[Step<'a>], _pretty: &mut Pretty<'a>) -> bool {
+        true
+    }
+
+    /// Called after the sub-trees.
+    fn leave(&mut self, _path: &[Step<'a>], _pretty: &mut Pretty<'a>) {}
+}
+
+impl<'a> Pretty<'a> {
+    /// The name of a record.
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Pretty::Record(xml) => Some(&xml.name),
+            _ => None,
+        }
+    }
+
+    /// Fields first, then children. [`Pretty::Linearized`] is printed
+    /// as is, so it has no sub-tree.
+    pub fn subtrees(&self) -> Vec<(Step<'a>, &Self)> {
+        use Pretty::*;
+        match self {
+            Record(xml) => (xml.fields.iter())
+                .map(|(k, v)| (Step::Field(k.clone()), v))
+                .chain(
+                    xml.children
+                        .iter()
+                        .enumerate()
+                        .map(|(i, c)| (Step::Child(i), c)),
+                )
+                .collect(),
+            Array(v) => v
+                .iter()
+                .enumerate()
+                .map(|(i, p)| (Step::Element(i), p))
+                .collect(),
+            Map(m) => (m.iter())
+                .map(|(k, v)| (Step::Field(k.clone()), v))
+                .collect(),
+            Seq(s) => (s.items.iter().enumerate())
+                .map(|(i, p)| (Step::Element(i), p))
+                .collect(),
+            Table(t) => (t.rows.iter().enumerate())
+                .flat_map(|(i, row)| {
+                    row.iter()
+                        .enumerate()
+                        .map(move |(j, p)| (Step::Cell(i, j), p))
+                })
+                .collect(),
+            Text(..) | Scalar(..) | Elided(..) | Linearized(..) | Ref(..) => vec![],
+        }
+    }
+
+    /// Same as [`Pretty::subtrees`].
+    pub fn subtrees_mut(&mut self) -> Vec<(Step<'a>, &mut Self)> {
+        use Pretty::*;
+        match self {
+            Record(xml) => (xml.fields.iter_mut())
+                .map(|(k, v)| (Step::Field(k.clone()), v))
+                .chain(
+                    xml.children
+                        .iter_mut()
+                        .enumerate()
+                        .map(|(i, c)| (Step::Child(i), c)),
+                )
+                .collect(),
+            Array(v) => (v.iter_mut().enumerate())
+                .map(|(i, p)| (Step::Element(i), p))
+                .collect(),
+            Map(m) => (m.iter_mut())
+                .map(|(k, v)| (Step::Field(k.clone()), v))
+                .collect(),
+            Seq(s) => (s.items.iter_mut().enumerate())
+                .map(|(i, p)| (Step::Element(i), p))
+                .collect(),
+            Table(t) => (t.rows.iter_mut().enumerate())
+                .flat_map(|(i, row)| {
+                    (row.iter_mut().enumerate()).map(move |(j, p)| (Step::Cell(i, j), p))
+                })
+                .collect(),
+            Text(..) | Scalar(..) | Elided(..) | Linearized(..) | Ref(..) => vec![],
+        }
+    }
+
+    pub fn walk(&self, visitor: &mut impl Visitor<'a>) {
+        self.walk_at(&mut vec![], visitor);
+    }
+
+    fn walk_at(&self, path: &mut Path<'a>, visitor: &mut impl Visitor<'a>) {
+        if visitor.enter(path, self) {
+            for (step, sub) in self.subtrees() {
+                path.push(step);
+                sub.walk_at(path, visitor);
+                path.pop();
+            }
+        }
+        visitor.leave(path, self);
+    }
+
+    /// Sub-trees are visited after [`VisitorMut::enter`] changed them.
+    pub fn walk_mut(&mut self, visitor: &mut impl VisitorMut<'a>) {
+        self.walk_mut_at(&mut vec![], visitor);
+    }
+
+    fn walk_mut_at(&mut self, path: &mut Path<'a>, visitor: &mut impl VisitorMut<'a>) {
+        if visitor.enter(path, self) {
+            for (step, sub) in self.subtrees_mut() {
+                path.push(step);
+                sub.walk_mut_at(path, visitor);
+                path.pop();
+            }
+        }
+        visitor.leave(path, self);
+    }
+
+    /// Every tree with its path, parents before their sub-trees.
+    pub fn pre_order(&self) -> PreOrder<'_, 'a> {
+        PreOrder {
+            stack: vec![(vec![], self)],
+        }
+    }
+
+    /// Every tree with its path, sub-trees before their parents.
+    pub fn post_order(&self) -> PostOrder<'_, 'a> {
+        PostOrder {
+            stack: vec![(vec![], self, false)],
+        }
+    }
+
+    /// Bottom-up: `f` gets a tree and the results of its sub-trees.
+    pub fn fold<T>(&self, f: &mut impl FnMut(&Self, Vec<T>) -> T) -> T {
+        let subs = (self.subtrees().into_iter())
+            .map(|(_, sub)| sub.fold(f))
+            .collect();
+        f(self, subs)
+    }
+
+    /// Bottom-up: `f` gets every tree after its sub-trees were replaced.
+    pub fn map(mut self, mut f: impl FnMut(Self) -> Self) -> Self {
+        self.map_in_place(&mut f);
+        self
+    }
+
+    fn map_in_place(&mut self, f: &mut impl FnMut(Self) -> Self) {
+        for (_, sub) in self.subtrees_mut() {
+            sub.map_in_place(f);
+        }
+        let this = mem::replace(self, Pretty::Array(vec![]));
+        *self = f(this);
+    }
+
+    /// Like [`Pretty::map`], only for records.
+    pub fn map_records(self, mut f: impl FnMut(XmlNode<'a>) -> XmlNode<'a>) -> Self {
+        self.map(|p| match p {
+            Pretty::Record(xml) => Pretty::Record(f(xml)),
+            p => p,
+        })
+    }
+}
+
+pub struct PreOrder<'b, 'a> {
+    stack: Vec<(Path<'a>, &'b Pretty<'a>)>,
+}
+
+impl<'b, 'a> Iterator for PreOrder<'b, 'a> {
+    type Item = (Path<'a>, &'b Pretty<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (path, pretty) = self.stack.pop()?;
+        for (step, sub) in pretty.subtrees().into_iter().rev() {
+            let mut sub_path = path.clone();
+            sub_path.push(step);
+            self.stack.push((sub_path, sub));
+        }
+        Some((path, pretty))
+    }
+}
+
+pub struct PostOrder<'b, 'a> {
+    /// The flag is set once the sub-trees are on the stack.
+    stack: Vec<(Path<'a>, &'b Pretty<'a>, bool)>,
+}
+
+impl<'b, 'a> Iterator for PostOrder<'b, 'a> {
+    type Item = (Path<'a>, &'b Pretty<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (path, pretty, expanded) = self.stack.pop()?;
+            if expanded {
+                return Some((path, pretty));
+            }
+            let subs = pretty.subtrees();
+            self.stack.push((path.clone(), pretty, true));
+            for (step, sub) in subs.into_iter().rev() {
+                let mut sub_path = path.clone();
+                sub_path.push(step);
+                self.stack.push((sub_path, sub, false));
+            }
+        }
+    }
+}
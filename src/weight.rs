@@ -0,0 +1,151 @@
+use std::hash::{Hash, Hasher};
+use std::iter::repeat_n;
+
+use crate::{metrics::metrics_count, Pretty, XmlNode};
+
+/// A cost of a node, like its time or its number of rows.
+/// Only the ratio to the largest weight of the tree is shown.
+#[derive(Clone, Copy, Debug)]
+pub struct Weight(pub f64);
+
+impl PartialEq for Weight {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_bits() == other.0.to_bits()
+    }
+}
+
+impl Eq for Weight {}
+
+impl Hash for Weight {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state);
+    }
+}
+
+/// How the weights are printed, in a column after all the metrics.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WeightStyle {
+    Hidden,
+    /// A bar of at most this many characters.
+    Bar(usize),
+    /// Like `42.0%`.
+    Percent,
+}
+
+impl Default for WeightStyle {
+    fn default() -> Self {
+        Self::Bar(8)
+    }
+}
+
+const EIGHTHS: [char; 7] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+/// Left-aligned, so that the bars of a column start at the same place.
+fn bar(ratio: f64, width: usize) -> String {
+    let eighths = (ratio * (width * 8) as f64).round() as usize;
+    let mut s: String = repeat_n('█', eighths / 8).collect();
+    if let Some(&c) = (eighths % 8).checked_sub(1).and_then(|i| EIGHTHS.get(i)) {
+        s.push(c);
+    }
+    let len = s.chars().count();
+    s.extend(repeat_n(' ', width.saturating_sub(len)));
+    s
+}
+
+/// Green, yellow, then red as the weight gets closer to the largest one.
+fn heat(ratio: f64) -> &'static str {
+    match ratio {
+        r if r < 1. / 3. => "32",
+        r if r < 2. / 3. => "33",
+        _ => "31",
+    }
+}
+
+struct Shown {
+    style: WeightStyle,
+    max: f64,
+    colored: bool,
+    /// Where the weights go in [`XmlNode::metrics`].
+    column: usize,
+}
+
+impl Shown {
+    fn indicator(&self, Weight(w): Weight) -> String {
+        let ratio = (w / self.max).clamp(0., 1.);
+        let s = match self.style {
+            WeightStyle::Bar(width) => bar(ratio, width),
+            _ => format!("{:.1}%", ratio * 100.),
+        };
+        if self.colored {
+            format!("\x1b[{}m{}\x1b[0m", heat(ratio), s)
+        } else {
+            s
+        }
+    }
+
+    fn xml<'a>(&self, xml: &XmlNode<'a>) -> XmlNode<'a> {
+        let fields = (xml.fields.iter())
+            .map(|(k, v)| (k.clone(), self.pretty(v)))
+            .collect();
+        let children = xml.children.iter().map(|c| self.pretty(c)).collect();
+        let mut res = xml.rebuild(fields, children);
+        if let Some(w) = xml.weight {
+            res.metrics.resize(self.column, "".into());
+            res.metrics.push(self.indicator(w).into());
+        }
+        res
+    }
+
+    fn pretty<'a>(&self, pretty: &Pretty<'a>) -> Pretty<'a> {
+        use Pretty::*;
+        match pretty {
+            Record(xml) => Record(self.xml(xml)),
+            Array(v) => Array(v.iter().map(|p| self.pretty(p)).collect()),
+            Map(m) => Map(m.map_values(|p| self.pretty(p))),
+            Seq(s) => Seq(s.map_items(|p| self.pretty(p))),
+            Table(t) => Table(t.map_cells(|p| self.pretty(p))),
+            Text(..) | Scalar(..) | Elided(..) | Linearized(..) | Ref(..) => pretty.clone(),
+        }
+    }
+}
+
+impl<'a> Pretty<'a> {
+    /// The largest weight in the tree, if there is a positive one.
+    pub fn max_weight(&self) -> Option<f64> {
+        use Pretty::*;
+        let max = |it: &mut dyn Iterator<Item = &Pretty>| {
+            it.filter_map(|p| p.max_weight()).reduce(f64::max)
+        };
+        let res = match self {
+            Record(xml) => {
+                let own = xml.weight.map(|Weight(w)| w);
+                let subs = max(&mut xml.fields.iter().map(|(_, v)| v).chain(&xml.children));
+                own.into_iter().chain(subs).reduce(f64::max)
+            }
+            Array(v) => max(&mut v.iter()),
+            Map(m) => max(&mut m.values()),
+            Seq(s) => max(&mut s.items.iter()),
+            Table(t) => max(&mut t.rows.iter().flatten()),
+            Linearized(p, _) => p.max_weight(),
+            Text(..) | Scalar(..) | Elided(..) | Ref(..) => None,
+        };
+        res.filter(|&w| w > 0.)
+    }
+
+    /// Appends the weight of every record to its metrics, relative to `max`,
+    /// which is usually the [`Pretty::max_weight`] of the whole tree.
+    /// Records with fewer metrics than others get empty ones before it,
+    /// so that the weights are a column of their own.
+    pub fn show_weights(&self, style: WeightStyle, max: f64, colored: bool) -> Self {
+        if style == WeightStyle::Hidden {
+            return self.clone();
+        }
+        let shown = Shown {
+            style,
+            max,
+            colored,
+            column: metrics_count(self),
+        };
+        shown.pretty(self)
+    }
+}
@@ -0,0 +1,477 @@
+//! Real XML. Elements become records, attributes become fields,
+//! and child elements and texts become children.
+//! [`PrettyConfig::xml`] goes the other way.
+//!
+//! ```rust
+//! use pretty_xmlish::Pretty;
+//! let xml = r#"<?xml version="1.0"?>
+//! <Exchange dist="Single">
+//!   <!-- a comment -->
+//!   <Scan table="t1" filter="a &lt; 1"/>
+//!   <note><![CDATA[<raw>]]></note>
+//! </Exchange>"#;
+//! let pretty = Pretty::parse_xml(xml).unwrap();
+//! assert_eq!(pretty, Pretty::simple_record("Exchange",
+//!     vec![("dist", "Single".into())],
+//!     vec![
+//!         Pretty::childless_record("Scan", vec![("table", "t1".into()), ("filter", "a < 1".into())]),
+//!         Pretty::fieldless_record("note", vec!["<raw>".into()]),
+//!     ],
+//! ));
+//! ```
+
+use std::{borrow::Cow, collections::HashSet};
+
+use crate::{
+    elision::ellipsis,
+    parse::{ParseError, ParseResult},
+    Pretty, PrettyConfig, Str, XmlNode,
+};
+
+struct XmlReader<'s> {
+    src: &'s str,
+    pos: usize,
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':')
+}
+
+impl<'s> XmlReader<'s> {
+    fn rest(&self) -> &'s str {
+        &self.src[self.pos..]
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> ParseResult<T> {
+        Err(ParseError {
+            pos: self.pos,
+            message: message.into(),
+        })
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        let ok = self.rest().starts_with(s);
+        if ok {
+            self.pos += s.len();
+        }
+        ok
+    }
+
+    fn expect(&mut self, s: &str) -> ParseResult<()> {
+        if self.eat(s) {
+            Ok(())
+        } else {
+            self.error(format!("expected `{}`", s))
+        }
+    }
+
+    fn skip_ws(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// Everything until `end`, which is consumed.
+    fn until(&mut self, end: &str) -> ParseResult<&'s str> {
+        match self.rest().find(end) {
+            Some(i) => {
+                let s = &self.rest()[..i];
+                self.pos += i + end.len();
+                Ok(s)
+            }
+            None => self.error(format!("expected `{}`", end)),
+        }
+    }
+
+    fn name(&mut self) -> ParseResult<&'s str> {
+        let rest = self.rest();
+        let len = rest.find(|c| !is_name_char(c)).unwrap_or(rest.len());
+        if len == 0 {
+            return self.error("expected a name");
+        }
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    /// Skips comments, processing instructions and the doctype.
+    fn skip_misc(&mut self) -> ParseResult<bool> {
+        if self.eat("<!--") {
+            self.until("-->")?;
+        } else if self.eat("<?") {
+            self.until("?>")?;
+        } else if self.eat("<!DOCTYPE") {
+            // The internal subset may contain `>`
+            let mut depth = 0;
+            for (i, c) in self.rest().char_indices() {
+                match c {
+                    '[' => depth += 1,
+                    ']' => depth -= 1,
+                    '>' if depth == 0 => {
+                        self.pos += i + 1;
+                        return Ok(true);
+                    }
+                    _ => {}
+                }
+            }
+            return self.error("unclosed doctype");
+        } else {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    fn unescape(&self, s: &'s str, pos: usize) -> ParseResult<Str<'s>> {
+        if !s.contains('&') {
+            return Ok(s.into());
+        }
+        let mut res = String::with_capacity(s.len());
+        let mut rest = s;
+        while let Some(i) = rest.find('&') {
+            res.push_str(&rest[..i]);
+            rest = &rest[i..];
+            let err_pos = pos + (s.len() - rest.len());
+            let end = rest.find(';').ok_or_else(|| ParseError {
+                pos: err_pos,
+                message: "unterminated entity".into(),
+            })?;
+            let c = match &rest[1..end] {
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "amp" => Some('&'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                e => (e.strip_prefix("#x"))
+                    .map(|h| u32::from_str_radix(h, 16))
+                    .or_else(|| e.strip_prefix('#').map(str::parse))
+                    .and_then(Result::ok)
+                    .and_then(char::from_u32),
+            };
+            res.push(c.ok_or_else(|| ParseError {
+                pos: err_pos,
+                message: format!("unknown entity `{}`", &rest[..=end]),
+            })?);
+            rest = &rest[end + 1..];
+        }
+        res.push_str(rest);
+        Ok(res.into())
+    }
+
+    fn attributes(&mut self) -> ParseResult<Vec<(Str<'s>, Pretty<'s>)>> {
+        let mut fields = vec![];
+        loop {
+            self.skip_ws();
+            if self.rest().starts_with('>') || self.rest().starts_with("/>") {
+                return Ok(fields);
+            }
+            let key = self.name()?;
+            self.skip_ws();
+            self.expect("=")?;
+            self.skip_ws();
+            let quote = match self.rest().chars().next() {
+                Some(q @ ('"' | '\'')) => q,
+                _ => return self.error("expected a quoted value"),
+            };
+            self.pos += 1;
+            let start = self.pos;
+            let value = self.until(&quote.to_string())?;
+            fields.push((key.into(), Pretty::Text(self.unescape(value, start)?)));
+        }
+    }
+
+    /// After the `<`.
+    fn element(&mut self) -> ParseResult<Pretty<'s>> {
+        let name = self.name()?;
+        let fields = self.attributes()?;
+        if self.eat("/>") {
+            return Ok(Pretty::Record(XmlNode::new(
+                name.into(),
+                fields.into(),
+                vec![],
+            )));
+        }
+        self.expect(">")?;
+        let mut children = vec![];
+        let mut text: Option<Str<'s>> = None;
+        let flush_text = |text: &mut Option<Str<'s>>, children: &mut Vec<Pretty<'s>>| {
+            if let Some(t) = text.take() {
+                children.push(Pretty::Text(t));
+            }
+        };
+        loop {
+            if self.eat("</") {
+                let start = self.pos;
+                if self.name()? != name {
+                    self.pos = start;
+                    return self.error(format!("expected `</{}>`", name));
+                }
+                self.skip_ws();
+                self.expect(">")?;
+                break;
+            }
+            let piece = if self.eat("<![CDATA[") {
+                Cow::Borrowed(self.until("]]>")?)
+            } else if self.skip_misc()? {
+                continue;
+            } else if self.eat("<") {
+                flush_text(&mut text, &mut children);
+                children.push(self.element()?);
+                continue;
+            } else if self.rest().is_empty() {
+                return self.error(format!("expected `</{}>`", name));
+            } else {
+                let start = self.pos;
+                let raw = self.rest();
+                let raw = &raw[..raw.find('<').unwrap_or(raw.len())];
+                self.pos += raw.len();
+                let trimmed = raw.trim();
+                if trimmed.is_empty() {
+                    continue;
+                }
+                self.unescape(trimmed, start + (raw.len() - raw.trim_start().len()))?
+            };
+            // Text and CDATA next to each other are one text
+            text = Some(match text.take() {
+                Some(t) => (t.into_owned() + &piece).into(),
+                None => piece,
+            });
+        }
+        flush_text(&mut text, &mut children);
+        Ok(Pretty::Record(XmlNode::new(
+            name.into(),
+            fields.into(),
+            children,
+        )))
+    }
+}
+
+impl<'s> Pretty<'s> {
+    /// Reads an XML document, see the [module docs](crate::xml).
+    /// Comments, processing instructions and the doctype are skipped,
+    /// and so is the whitespace around texts.
+    pub fn parse_xml(src: &'s str) -> ParseResult<Self> {
+        let mut reader = XmlReader { src, pos: 0 };
+        reader.eat("\u{feff}");
+        loop {
+            reader.skip_ws();
+            if !reader.skip_misc()? {
+                break;
+            }
+        }
+        reader.expect("<")?;
+        let root = reader.element()?;
+        loop {
+            reader.skip_ws();
+            if !reader.skip_misc()? {
+                break;
+            }
+        }
+        if !reader.rest().is_empty() {
+            return reader.error("unexpected content after the root element");
+        }
+        Ok(root)
+    }
+}
+
+/// Names that are not valid in XML get their bad characters replaced by `_`.
+fn xml_name(name: &str) -> Cow<'_, str> {
+    let valid_start = |c: char| c.is_alphabetic() || c == '_';
+    match name.chars().next() {
+        Some(c) if valid_start(c) && name.chars().all(is_name_char) => Cow::Borrowed(name),
+        None => Cow::Borrowed("_"),
+        Some(c) => {
+            let prefix = if valid_start(c) { "" } else { "_" };
+            let rest = name.chars().map(|c| if is_name_char(c) { c } else { '_' });
+            Cow::Owned(prefix.chars().chain(rest).collect())
+        }
+    }
+}
+
+/// Texts and scalars, which can be attributes. String literals lose their quotes.
+fn leaf_text<'p>(pretty: &'p Pretty) -> Option<Cow<'p, str>> {
+    match pretty {
+        Pretty::Text(s) => Some(Cow::Borrowed(s)),
+        Pretty::Scalar(s) => Some(s.raw().into()),
+        _ => None,
+    }
+}
+
+/// Control characters other than whitespace cannot appear in XML 1.0,
+/// not even as references.
+fn is_forbidden(c: char) -> bool {
+    c.is_ascii_control() && !matches!(c, '\t' | '\n' | '\r')
+}
+
+fn escape(s: &str, in_attribute: bool, out: &mut String) {
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' if in_attribute => out.push_str("&quot;"),
+            '\n' if in_attribute => out.push_str("&#10;"),
+            '\t' if in_attribute => out.push_str("&#9;"),
+            '\r' => out.push_str("&#13;"),
+            c if is_forbidden(c) => out.push(char::REPLACEMENT_CHARACTER),
+            c => out.push(c),
+        }
+    }
+}
+
+struct XmlWriter<'o> {
+    out: &'o mut String,
+    /// Zero for everything on one line.
+    indent: usize,
+    started: bool,
+}
+
+impl XmlWriter<'_> {
+    fn begin_line(&mut self, depth: usize) {
+        if self.indent > 0 {
+            if self.started {
+                self.out.push('\n');
+            }
+            self.started = true;
+            self.out
+                .extend(std::iter::repeat_n(' ', depth * self.indent));
+        }
+    }
+
+    fn content(&mut self, pretty: &Pretty, depth: usize) {
+        use Pretty::*;
+        match pretty {
+            Text(s) => {
+                self.begin_line(depth);
+                escape(s, false, self.out);
+            }
+            Scalar(s) => {
+                self.begin_line(depth);
+                escape(&s.raw(), false, self.out);
+            }
+            Record(xml) => self.record(xml, depth),
+            Array(v) => v.iter().for_each(|p| self.element("item", p, depth)),
+            Seq(s) => (s.items.iter()).for_each(|p| self.element("item", p, depth)),
+            Map(m) => m.iter().for_each(|(k, v)| self.field(k, v, depth)),
+            Table(t) => {
+                for row in &t.rows {
+                    let fields = t.columns.iter().cloned().zip(row.iter().cloned());
+                    self.record(&XmlNode::new("row".into(), fields.collect(), vec![]), depth);
+                }
+            }
+            Linearized(p, _) => self.content(p, depth),
+            Ref(id) => {
+                self.begin_line(depth);
+                self.out.push_str(&format!("<ref id=\"{}\"/>", id));
+            }
+            Elided(n) => self.comment(&ellipsis(*n), depth),
+        }
+    }
+
+    /// `<name>pretty</name>`, texts are kept on the same line.
+    fn element(&mut self, name: &str, pretty: &Pretty, depth: usize) {
+        let name = xml_name(name);
+        self.begin_line(depth);
+        match leaf_text(pretty) {
+            Some(s) if s.is_empty() => self.out.push_str(&format!("<{}/>", name)),
+            Some(s) => {
+                self.out.push_str(&format!("<{}>", name));
+                escape(&s, false, self.out);
+                self.out.push_str(&format!("</{}>", name));
+            }
+            _ => {
+                self.out.push_str(&format!("<{}>", name));
+                self.content(pretty, depth + 1);
+                self.begin_line(depth);
+                self.out.push_str(&format!("</{}>", name));
+            }
+        }
+    }
+
+    /// `--` cannot appear in a comment, so dashes are kept apart.
+    fn comment(&mut self, s: &str, depth: usize) {
+        self.begin_line(depth);
+        self.out.push_str("<!-- ");
+        for c in s.chars() {
+            if c == '-' && self.out.ends_with('-') {
+                self.out.push(' ');
+            }
+            self.out.push(if is_forbidden(c) {
+                char::REPLACEMENT_CHARACTER
+            } else {
+                c
+            });
+        }
+        self.out.push_str(" -->");
+    }
+
+    /// A nested element named after the key.
+    fn field(&mut self, k: &str, v: &Pretty, depth: usize) {
+        if v.is_elided() {
+            self.content(v, depth);
+        } else {
+            self.element(k, v, depth);
+        }
+    }
+
+    fn record(&mut self, xml: &XmlNode, depth: usize) {
+        let name = xml_name(&xml.name);
+        self.begin_line(depth);
+        self.out.push('<');
+        self.out.push_str(&name);
+        // An attribute name can only be used once, the other fields are nested
+        let mut names = HashSet::new();
+        if let Some(id) = xml.id {
+            self.out.push_str(&format!(" id=\"{}\"", id));
+            names.insert(Cow::Borrowed("id"));
+        }
+        let mut nested = vec![];
+        for (k, v) in xml.fields.iter() {
+            match leaf_text(v) {
+                Some(s) if names.insert(xml_name(k)) => {
+                    self.out.push_str(&format!(" {}=\"", xml_name(k)));
+                    escape(&s, true, self.out);
+                    self.out.push('"');
+                }
+                _ => nested.push((k, v)),
+            }
+        }
+        if nested.is_empty() && xml.children.is_empty() && xml.notes.is_empty() {
+            self.out.push_str("/>");
+            return;
+        }
+        self.out.push('>');
+        for note in xml.notes.iter() {
+            self.comment(note, depth + 1);
+        }
+        for (k, v) in nested {
+            self.field(k, v, depth + 1);
+        }
+        for child in xml.children.iter() {
+            self.content(child, depth + 1);
+        }
+        self.begin_line(depth);
+        self.out.push_str(&format!("</{}>", name));
+    }
+}
+
+impl PrettyConfig {
+    /// Writes well-formed XML: records become elements, text fields become
+    /// attributes and other fields become nested elements, array elements
+    /// become `<item>`s. Records with an id get an `id` attribute, and
+    /// references become `<ref id=".."/>`. Notes become comments.
+    /// A field named like an attribute that is already there becomes a
+    /// nested element instead. There are no colors, and the control
+    /// characters that XML cannot represent are replaced by `�`.
+    /// Elements are indented by `indent`, or all on one line if it is zero.
+    pub fn xml(&self, out: &mut String, pretty: &Pretty) {
+        let pretty = self.preprocess_plain(pretty);
+        let mut writer = XmlWriter {
+            out,
+            indent: self.indent,
+            started: false,
+        };
+        match &*pretty {
+            // A document has exactly one root element
+            Pretty::Record(xml) => writer.record(xml, 0),
+            p => writer.element("root", p, 0),
+        }
+    }
+}
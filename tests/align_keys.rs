@@ -0,0 +1,110 @@
+use pretty_xmlish::{Pretty, PrettyConfig, Seq};
+
+fn scan() -> Pretty<'static> {
+    Pretty::record("Scan")
+        .field("id", "1")
+        .field("predicate", "x")
+        .field("a_very_long_key_name", "y")
+        .field("columns", Pretty::Array(vec!["a".into(), "b".into()]))
+        .build()
+}
+
+fn config() -> PrettyConfig {
+    PrettyConfig {
+        need_boundaries: false,
+        width: 20,
+        align_keys: Some(10),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn keys_over_the_cap_are_not_padded() {
+    let mut out = String::new();
+    config().unicode(&mut out, &scan());
+    assert_eq!(
+        out,
+        "Scan\n\
+         ├── id:        1\n\
+         ├── predicate: x\n\
+         ├── a_very_long_key_name: y\n\
+         └── columns:   [ a, b ]"
+    );
+
+    let mut out = String::new();
+    config().ascii(&mut out, &scan());
+    assert_eq!(
+        out,
+        "Scan {\n\
+        \x20   id:        1,\n\
+        \x20   predicate: x,\n\
+        \x20   a_very_long_key_name: y,\n\
+        \x20   columns:   [ a, b ]\n\
+         }"
+    );
+}
+
+/// Values start at the same column in both styles, unless they start below
+/// their key.
+#[test]
+fn same_padding_in_both_styles() {
+    let columns = |n| Pretty::Array((0..n).map(|i| format!("column_{}", i).into()).collect());
+    let tree = Pretty::record("Scan")
+        .field("id", "1")
+        .field("columns", columns(3))
+        .field(
+            "pred",
+            Seq::separated(" AND ", vec!["id > 1".into(), "id < 100".into()]),
+        )
+        .field("empty", Pretty::Array(vec![]))
+        .field("input", Pretty::record("Values").field("rows", columns(2)))
+        .build();
+    let mut config = PrettyConfig {
+        width: 24,
+        ..config()
+    };
+
+    let mut out = String::new();
+    config.unicode(&mut out, &tree);
+    assert_eq!(
+        out,
+        "Scan\n\
+         ├── id:      1\n\
+         ├── columns:\n\
+         │   ┌── column_0\n\
+         │   ├── column_1\n\
+         │   └── column_2\n\
+         ├── pred:\n\
+         │   ┌── id > 1 AND\n\
+         │   └── id < 100\n\
+         ├── empty:   []\n\
+         └── input:   Values\n    \
+             └── rows:\n        \
+                 ┌── column_0\n        \
+                 └── column_1"
+    );
+
+    let mut out = String::new();
+    config.ascii(&mut out, &tree);
+    assert_eq!(
+        out,
+        "Scan {\n\
+        \x20   id:      1,\n\
+        \x20   columns: [\n\
+        \x20       column_0,\n\
+        \x20       column_1,\n\
+        \x20       column_2\n\
+        \x20   ],\n\
+        \x20   pred:\n\
+        \x20       id > 1 AND\n\
+        \x20       id < 100,\n\
+        \x20   empty:   [],\n\
+        \x20   input:   Values {\n\
+        \x20       rows: [\n\
+        \x20           column_0,\n\
+        \x20           column_1\n\
+        \x20       ]\n\
+        \x20   }\n\
+         }"
+    );
+}
@@ -0,0 +1,105 @@
+use pretty_xmlish::{Pretty, PrettyConfig, RefError};
+
+fn scan(id: usize) -> Pretty<'static> {
+    Pretty::record("Scan").field("table", "t1").id(id).build()
+}
+
+#[test]
+fn refs_are_checked() {
+    let ok = Pretty::record("Join")
+        .child(scan(1))
+        .child(Pretty::Ref(1))
+        .build();
+    assert_eq!(ok.check_refs(), Ok(()));
+
+    let dangling = Pretty::record("Join")
+        .child(scan(1))
+        .child(Pretty::Ref(2))
+        .build();
+    assert_eq!(dangling.check_refs(), Err(RefError::Dangling(2)));
+
+    let twice = Pretty::record("Join").child(scan(1)).child(scan(1)).build();
+    assert_eq!(twice.check_refs(), Err(RefError::DuplicateId(1)));
+
+    let looping = Pretty::record("Loop").id(1).child(Pretty::Ref(1)).build();
+    assert_eq!(looping.check_refs(), Err(RefError::Cycle(1)));
+    assert_eq!(looping.inline_refs(), Err(RefError::Cycle(1)));
+}
+
+#[test]
+fn indirect_cycles_are_found() {
+    let b = Pretty::record("B").id(2).child(Pretty::Ref(1)).build();
+    let a = Pretty::record("A").id(1).child(b).build();
+    assert_eq!(a.check_refs(), Err(RefError::Cycle(1)));
+}
+
+#[test]
+fn refs_are_inlined() {
+    let dag = Pretty::record("Join")
+        .child(scan(1))
+        .child(Pretty::Ref(1))
+        .build();
+    let plain = Pretty::record("Scan").field("table", "t1").build();
+    let tree = Pretty::record("Join")
+        .child(plain.clone())
+        .child(plain)
+        .build();
+    assert_eq!(dag.inline_refs(), Ok(tree));
+}
+
+#[test]
+fn shared_subtrees_are_drawn_once() {
+    let shared = Pretty::record("Agg")
+        .child(Pretty::record("Scan").field("table", "t1"))
+        .build();
+    let join = Pretty::record("Join")
+        .child(shared.clone())
+        .child(shared)
+        .build();
+    let config = PrettyConfig {
+        dedup: true,
+        ..Default::default()
+    };
+    let mut out = String::new();
+    config.dot(&mut out, &join);
+    assert_eq!(
+        out,
+        "digraph {\n\
+        \x20 node [shape=box];\n\
+        \x20 n0 [label=\"Join\"];\n\
+        \x20 r1 [label=\"Agg [#1]\"];\n\
+        \x20 n1 [label=\"Scan\\ntable: t1\"];\n\
+        \x20 r1 -> n1;\n\
+        \x20 n0 -> r1;\n\
+        \x20 n0 -> r1;\n\
+        }\n"
+    );
+}
+
+#[test]
+fn only_equal_subtrees_are_shared() {
+    let agg = |table, rows| {
+        Pretty::record("Agg")
+            .child(Pretty::record("Scan").field("table", table).metric(rows))
+            .build()
+    };
+    let tree = Pretty::record("Union")
+        .child(agg("t1", "rows=1"))
+        .child(agg("t1", "rows=2"))
+        .child(Pretty::record("Limit").child(agg("t1", "rows=1")))
+        .child(agg("t2", "rows=1"))
+        .child(agg("t1", "rows=1"))
+        .build();
+    let expected = Pretty::record("Union")
+        .child(
+            Pretty::record("Agg")
+                .id(1)
+                .child(Pretty::record("Scan").field("table", "t1").metric("rows=1")),
+        )
+        .child(agg("t1", "rows=2"))
+        .child(Pretty::record("Limit").child(Pretty::Ref(1)))
+        .child(agg("t2", "rows=1"))
+        .child(Pretty::Ref(1))
+        .build();
+    assert_eq!(tree.dedup(), expected);
+}
@@ -0,0 +1,39 @@
+use pretty_xmlish::{Pretty, PrettyConfig};
+
+fn scan(table: &'static str) -> Pretty<'static> {
+    Pretty::record("Scan").field("table", table).build()
+}
+
+#[test]
+fn changes_are_marked() {
+    let old = Pretty::record("Join")
+        .field("type", "Inner")
+        .field("cond", "a = b")
+        .child(scan("t1"))
+        .child(scan("t2"))
+        .build();
+    let new = Pretty::record("Join")
+        .field("type", "Left")
+        .field("limit", "10")
+        .child(scan("t1"))
+        .child(Pretty::record("Filter").child(scan("t2")))
+        .build();
+    let mut config = PrettyConfig {
+        need_boundaries: false,
+        width: 40,
+        ..Default::default()
+    };
+    let mut out = String::new();
+    config.unicode_diff(&mut out, &old, &new);
+    assert_eq!(
+        out,
+        "~ Join\n\
+         ├── ~ type: Inner → Left\n\
+         ├── - cond: a = b\n\
+         ├── + limit: 10\n\
+         ├── Scan { table: t1 }\n\
+         ├── - Scan { table: t2 }\n\
+         └── + Filter\n\
+        \x20   └── Scan { table: t2 }"
+    );
+}
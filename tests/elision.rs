@@ -0,0 +1,93 @@
+use pretty_xmlish::{Elision, Pretty, PrettyConfig};
+
+fn config(elision: Elision) -> PrettyConfig {
+    PrettyConfig {
+        elision,
+        need_boundaries: false,
+        ..Default::default()
+    }
+}
+
+fn numbers(n: i64) -> Vec<Pretty<'static>> {
+    (0..n).map(Pretty::int).collect()
+}
+
+#[test]
+fn one_line_is_elided() {
+    let elision = Elision {
+        max_elements: Some(1),
+        ..Default::default()
+    };
+    let mut out = String::new();
+    config(elision).one_line(&mut out, &Pretty::Array(numbers(5)));
+    assert_eq!(out, "[ 0, … 3 more, 4 ]");
+}
+
+#[test]
+fn elided_fields_have_no_key() {
+    let elision = Elision {
+        max_fields: Some(1),
+        ..Default::default()
+    };
+    let scan = Pretty::record("Scan")
+        .fields([("a", "1"), ("b", "2"), ("c", "3"), ("d", "4")])
+        .build();
+    let mut out = String::new();
+    config(elision).one_line(&mut out, &scan);
+    assert_eq!(out, "Scan { a: 1, … 2 more, d: 4 }");
+    assert_eq!(Pretty::parse_one_line(&out).unwrap(), scan.elide(&elision));
+}
+
+#[test]
+fn empty_keys_are_printed() {
+    let map = Pretty::parse_json(r#"{ "": 1, "a": 2 }"#).unwrap();
+    assert_eq!(map.to_one_line_string(false), "{ : 1, a: 2 }");
+    let elision = Elision {
+        max_fields: Some(1),
+        ..Default::default()
+    };
+    let map = Pretty::parse_json(r#"{ "": 1, "a": 2, "b": 3, "c": 4 }"#).unwrap();
+    let mut out = String::new();
+    config(elision).one_line(&mut out, &map);
+    assert_eq!(out, "{ : 1, … 2 more, c: 4 }");
+}
+
+#[test]
+fn elided_children_in_the_tree() {
+    let elision = Elision {
+        max_children: Some(1),
+        ..Default::default()
+    };
+    let children = ["a", "b", "c", "d"].map(|t| Pretty::record("Scan").field("table", t).build());
+    let union = Pretty::record("Union").children(children).build();
+    let mut out = String::new();
+    config(elision).unicode(&mut out, &union);
+    assert_eq!(
+        out,
+        "Union\n\
+         ├── Scan { table: a }\n\
+         ├── … 2 more\n\
+         └── Scan { table: d }"
+    );
+}
+
+#[test]
+fn elided_fields_in_the_tree() {
+    let elision = Elision {
+        max_fields: Some(1),
+        ..Default::default()
+    };
+    let scan = Pretty::record("Scan")
+        .fields([("a", "1"), ("b", "2"), ("c", "3"), ("d", "4")])
+        .build();
+    let mut config = PrettyConfig {
+        width: 10,
+        ..config(elision)
+    };
+    let mut out = String::new();
+    config.clone().unicode(&mut out, &scan);
+    assert_eq!(out, "Scan\n├── a: 1\n├── … 2 more\n└── d: 4");
+    let mut out = String::new();
+    config.ascii(&mut out, &scan);
+    assert_eq!(out, "Scan {\n    a: 1,\n    … 2 more,\n    d: 4\n}");
+}
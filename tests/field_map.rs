@@ -0,0 +1,86 @@
+use pretty_xmlish::{FieldMap, Pretty};
+
+/// Every field can be looked up at its position, and elided ones cannot.
+fn assert_consistent(map: &FieldMap) {
+    for (i, (k, v)) in map.iter().enumerate() {
+        if v.is_elided() {
+            assert_ne!(map.position(k), Some(i), "{:?}", map);
+        } else {
+            assert_eq!(map.position(k), Some(i), "{:?}", map);
+            assert_eq!(map.get(k), Some(v));
+        }
+    }
+}
+
+fn keys<'m>(map: &'m FieldMap) -> Vec<&'m str> {
+    map.keys().collect()
+}
+
+#[test]
+fn insert_remove_and_entry() {
+    let mut map = FieldMap::from([("b", "1".into()), ("a", "2".into()), ("c", "3".into())]);
+    assert_consistent(&map);
+    assert_eq!(map.insert("a".into(), "4".into()), Some("2".into()));
+    assert_eq!(keys(&map), ["b", "a", "c"]);
+    assert_eq!(map.remove("b"), Some("1".into()));
+    assert_eq!(map.remove("b"), None);
+    assert_consistent(&map);
+    assert_eq!(map.insert("b".into(), "5".into()), None);
+    assert_eq!(keys(&map), ["a", "c", "b"]);
+    assert_consistent(&map);
+
+    map.entry("c".into()).and_modify(|v| *v = "6".into());
+    map.entry("d".into()).or_insert("7".into());
+    assert_eq!(keys(&map), ["a", "c", "b", "d"]);
+    assert_eq!(map["c"], "6".into());
+    assert_consistent(&map);
+    match map.entry("a".into()) {
+        pretty_xmlish::field_map::Entry::Occupied(e) => assert_eq!(e.remove(), "4".into()),
+        _ => unreachable!(),
+    }
+    assert_eq!(keys(&map), ["c", "b", "d"]);
+    assert_consistent(&map);
+}
+
+#[test]
+fn elided_fields_are_not_indexed() {
+    let mut map = FieldMap::from([("a", "1".into())]);
+    assert_eq!(map.insert("a".into(), Pretty::Elided(3)), None);
+    assert_eq!(map.insert("".into(), Pretty::Elided(2)), None);
+    map.entry("x".into()).or_insert(Pretty::Elided(1));
+    assert_eq!(keys(&map), ["a", "a", "", "x"]);
+    assert_eq!(map["a"], "1".into());
+    assert!(!map.contains_key("") && !map.contains_key("x"));
+    assert_consistent(&map);
+
+    let extended: FieldMap = vec![("a", "1".into()), ("a", Pretty::Elided(3))].into();
+    assert_eq!(extended, map.iter().take(2).cloned().collect());
+}
+
+#[test]
+fn duplicates_in_lists() {
+    let map = FieldMap::from(vec![
+        ("a", "1".into()),
+        ("b", "2".into()),
+        ("a", "3".into()),
+    ]);
+    assert_eq!(keys(&map), ["a", "b"]);
+    assert_eq!(map["a"], "3".into());
+    assert_consistent(&map);
+}
+
+#[test]
+fn map_values_reindexes() {
+    let map = FieldMap::from([("a", "1".into()), ("b", "2".into())]);
+    let elided = map.map_values(|v| match v {
+        Pretty::Text(t) if t == "1" => Pretty::Elided(1),
+        v => v.clone(),
+    });
+    assert_eq!(keys(&elided), ["a", "b"]);
+    assert!(!elided.contains_key("a"));
+    assert_consistent(&elided);
+
+    let back = elided.map_values(|_| "0".into());
+    assert_eq!(back.get("a"), Some(&"0".into()));
+    assert_consistent(&back);
+}
@@ -0,0 +1,39 @@
+use pretty_xmlish::Pretty;
+
+#[test]
+fn numbers() {
+    let parse = |s| Pretty::parse_json(s).ok();
+    assert_eq!(parse("0"), Some(Pretty::int(0)));
+    assert_eq!(parse("-12"), Some(Pretty::int(-12)));
+    assert_eq!(parse("1.5e3"), Some(Pretty::float(1500.)));
+    assert_eq!(parse("-0.25E-2"), Some(Pretty::float(-0.0025)));
+    assert_eq!(parse("1e400"), None);
+    for bad in [
+        "NaN",
+        "inf",
+        "-infinity",
+        "+1",
+        ".5",
+        "1.",
+        "01",
+        "-",
+        "1e",
+        "0x10",
+    ] {
+        assert_eq!(parse(bad), None, "{}", bad);
+    }
+}
+
+#[test]
+fn unicode_escapes() {
+    let parse = |s| Pretty::parse_json(s).ok();
+    assert_eq!(parse(r#""\u00e9t\u00E9""#), Some("été".into()));
+    assert_eq!(parse(r#""\ud83d\ude00!""#), Some("😀!".into()));
+    // Lone surrogates, also before another escape
+    assert_eq!(parse(r#""\ud83d!""#), Some("\u{FFFD}!".into()));
+    assert_eq!(parse(r#""\ude00\ud83d""#), Some("\u{FFFD}\u{FFFD}".into()));
+    assert_eq!(parse(r#""\ud83d\u0041""#), Some("\u{FFFD}A".into()));
+    for bad in [r#""\u12""#, r#""\u+123""#, r#""\uzzzz""#] {
+        assert_eq!(parse(bad), None, "{}", bad);
+    }
+}
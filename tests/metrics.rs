@@ -0,0 +1,98 @@
+use pretty_xmlish::{Pretty, PrettyConfig, WeightStyle};
+
+fn agg() -> Pretty<'static> {
+    Pretty::record("Agg")
+        .metric("rows=10k")
+        .metric("12.3ms")
+        .child(Pretty::record("Scan").field("table", "t1").metric("rows=5"))
+        .build()
+}
+
+fn render(need_boundaries: bool) -> String {
+    let mut config = PrettyConfig {
+        need_boundaries,
+        ..Default::default()
+    };
+    let mut out = String::new();
+    config.unicode(&mut out, &agg());
+    out
+}
+
+#[test]
+fn columns_without_boundaries() {
+    assert_eq!(
+        render(false),
+        "Agg                     rows=10k  12.3ms\n\
+         └── Scan { table: t1 }    rows=5"
+    );
+}
+
+#[test]
+fn columns_with_boundaries() {
+    assert_eq!(
+        render(true),
+        "+------------------------------------------+\n\
+         | Agg                     rows=10k  12.3ms |\n\
+         | └── Scan { table: t1 }    rows=5         |\n\
+         +------------------------------------------+"
+    );
+}
+
+#[test]
+fn weights_have_their_own_column() {
+    let tree = Pretty::record("Agg")
+        .metric("rows=10k")
+        .metric("12.3ms")
+        .weight(2.0)
+        .child(
+            Pretty::record("Scan")
+                .field("table", "t1")
+                .metric("rows=5")
+                .weight(1.0),
+        )
+        .build();
+    let mut config = PrettyConfig {
+        need_boundaries: false,
+        weights: WeightStyle::Bar(4),
+        ..Default::default()
+    };
+    let mut out = String::new();
+    config.unicode(&mut out, &tree);
+    assert_eq!(
+        out,
+        "Agg                     rows=10k  12.3ms  ████\n\
+         └── Scan { table: t1 }    rows=5          ██"
+    );
+
+    // The weights are a column of the metrics
+    config.show_metrics = false;
+    let mut out = String::new();
+    config.unicode(&mut out, &tree);
+    assert_eq!(out, "Agg\n└── Scan { table: t1 }");
+}
+
+#[test]
+fn records_inside_a_line_take_no_room() {
+    let scan = |metric| Pretty::record("Scan").field("table", "t1").metric(metric);
+    let tree = Pretty::record("Agg")
+        .metric("rows=10")
+        .field("input", scan("rows=123456789"))
+        .child(
+            Pretty::record("Filter")
+                .metric("rows=5")
+                .child(scan("rows=8")),
+        )
+        .build();
+    let mut config = PrettyConfig {
+        need_boundaries: false,
+        ..Default::default()
+    };
+    let mut out = String::new();
+    config.unicode(&mut out, &tree);
+    assert_eq!(
+        out,
+        "Agg { input: Scan { table: t1 } }  rows=10\n\
+         └── Filter                          rows=5\n    \
+             └── Scan { table: t1 }          rows=8"
+    );
+}
@@ -0,0 +1,132 @@
+use pretty_xmlish::{Pretty, PrettyConfig};
+
+fn scan() -> Pretty<'static> {
+    Pretty::record("S")
+        .field("a", "b")
+        .note("pushed down from Filter")
+        .child(Pretty::record("Scan").field("table", "t1"))
+        .build()
+}
+
+fn config(colors: bool) -> PrettyConfig {
+    PrettyConfig {
+        width: 30,
+        colors,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn notes_are_counted_in_the_width() {
+    let mut out = String::new();
+    config(false).unicode(&mut out, &scan());
+    assert_eq!(
+        out,
+        "+--------------------------------+\n\
+         | S                              |\n\
+         | │   -- pushed down from Filter |\n\
+         | ├── a: b                       |\n\
+         | └── Scan { table: t1 }         |\n\
+         +--------------------------------+"
+    );
+
+    let mut out = String::new();
+    config(false).ascii(&mut out, &scan());
+    assert_eq!(
+        out,
+        "+--------------------------------+\n\
+         | S {                            |\n\
+         |     -- pushed down from Filter |\n\
+         |     a: b                       |\n\
+         | }                              |\n\
+         |     Scan { table: t1 }         |\n\
+         +--------------------------------+"
+    );
+}
+
+/// Without the escape sequences.
+fn plain(s: &str) -> String {
+    let mut out = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|&c| c == 'm');
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[test]
+fn dimmed_notes_are_counted_in_the_width() {
+    let render = |colors, unicode| {
+        let mut out = String::new();
+        if unicode {
+            config(colors).unicode(&mut out, &scan());
+        } else {
+            config(colors).ascii(&mut out, &scan());
+        }
+        out
+    };
+    for unicode in [true, false] {
+        let dimmed = render(true, unicode);
+        assert!(dimmed.contains("-- \x1b[2mpushed down from Filter\x1b[0m"));
+        assert_eq!(plain(&dimmed), render(false, unicode));
+    }
+}
+
+#[test]
+fn notes_are_read_back() {
+    let scan = Pretty::record("Scan")
+        .field("a", "1")
+        .note("hint")
+        .note("missing statistics")
+        .build();
+    for reduced_ws in [false, true] {
+        let s = scan.to_one_line_string(reduced_ws);
+        assert_eq!(s, "Scan { a: 1 } -- hint -- missing statistics");
+        assert_eq!(Pretty::parse_one_line(&s), Ok(scan.clone()));
+    }
+    let nested = Pretty::Array(vec![scan, "b".into()]);
+    let s = nested.to_one_line_string(false);
+    assert_eq!(Pretty::parse_one_line(&s), Ok(nested));
+}
+
+#[test]
+fn notes_are_tooltips() {
+    let join = Pretty::record("Join")
+        .note("missing statistics")
+        .note("a \"hint\"")
+        .child(Pretty::record("Scan").field("table", "t1"))
+        .build();
+    let config = PrettyConfig::default();
+    let mut out = String::new();
+    config.dot(&mut out, &join);
+    assert!(
+        out.contains(
+            "n0 [label=\"Join\\n-- missing statistics\\n-- a \\\"hint\\\"\", \
+             tooltip=\"missing statistics\\na \\\"hint\\\"\"];"
+        ),
+        "{}",
+        out
+    );
+    assert!(out.contains("n1 [label=\"Scan\\ntable: t1\"];"), "{}", out);
+
+    let mut out = String::new();
+    config.html(&mut out, &join);
+    assert_eq!(
+        out,
+        "<ul class=\"pretty-xmlish\">\n\
+        \x20 <li><span title=\"missing statistics&#10;a &quot;hint&quot;\">Join</span>\n\
+        \x20   <ul>\n\
+        \x20     <li><span>Scan</span>\n\
+        \x20       <ul>\n\
+        \x20         <li>table: t1</li>\n\
+        \x20       </ul>\n\
+        \x20     </li>\n\
+        \x20   </ul>\n\
+        \x20 </li>\n\
+         </ul>\n"
+    );
+}
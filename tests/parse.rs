@@ -0,0 +1,86 @@
+//! `parse_one_line(to_one_line_string(p)) == p` for random trees of the
+//! subset the one-line format can tell apart, see [`pretty_xmlish::parse`]:
+//! texts without brackets, commas or surrounding spaces (scalars would be
+//! read back as texts), records with fields, maybe an id and notes,
+//! arrays, maps, references and elided entries.
+
+use pretty_xmlish::{FieldMap, Pretty, XmlNode};
+
+/// xorshift64, so that failures can be reproduced from the seed.
+struct Rng(u64);
+
+impl Rng {
+    fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n as u64) as usize
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len())]
+    }
+
+    fn text(&mut self) -> Pretty<'static> {
+        const WORDS: &[&str] = &[
+            "t1", "$0", "a.b", "x_1", "NULL", "=", ">=", "AND", "é", "f(a)",
+        ];
+        let words: Vec<_> = (0..1 + self.below(3)).map(|_| self.pick(WORDS)).collect();
+        Pretty::Text(words.join(" ").into())
+    }
+
+    fn fields(&mut self, depth: usize) -> Vec<(String, Pretty<'static>)> {
+        (0..1 + self.below(3))
+            .map(|i| match self.below(6) {
+                0 => (String::new(), Pretty::Elided(1 + self.below(9))),
+                _ => (
+                    format!("{}{}", self.pick(&["k", "key_", "Col"]), i),
+                    self.tree(depth),
+                ),
+            })
+            .collect()
+    }
+
+    fn tree(&mut self, depth: usize) -> Pretty<'static> {
+        let leaf = depth == 0;
+        match self.below(if leaf { 3 } else { 6 }) {
+            0 | 1 => self.text(),
+            2 => Pretty::Ref(self.below(100)),
+            3 => {
+                let name = self.pick(&["Scan", "BatchHashJoin", "Agg"]);
+                let mut xml = XmlNode::new(name.into(), self.fields(depth - 1).into(), vec![]);
+                xml.id = (self.below(2) == 0).then(|| self.below(10));
+                for _ in 0..self.below(3) {
+                    if let Pretty::Text(note) = self.text() {
+                        xml.notes.push(note);
+                    }
+                }
+                Pretty::Record(xml)
+            }
+            4 => Pretty::Array(
+                (0..self.below(4))
+                    .map(|_| match self.below(6) {
+                        0 => Pretty::Elided(1 + self.below(9)),
+                        _ => self.tree(depth - 1),
+                    })
+                    .collect(),
+            ),
+            _ => Pretty::Map(match self.below(5) {
+                0 => FieldMap::default(),
+                _ => self.fields(depth - 1).into(),
+            }),
+        }
+    }
+}
+
+#[test]
+fn random_trees_round_trip() {
+    for seed in 1..=2000 {
+        let tree = Rng(seed).tree(4);
+        for reduced_ws in [false, true] {
+            let s = tree.to_one_line_string(reduced_ws);
+            let parsed = Pretty::parse_one_line(&s);
+            assert_eq!(parsed.as_ref(), Ok(&tree), "seed {}: {}", seed, s);
+        }
+    }
+}
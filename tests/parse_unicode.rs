@@ -0,0 +1,54 @@
+use pretty_xmlish::{FieldMap, Pretty, PrettyConfig};
+
+#[test]
+fn broken_maps_are_read_back() {
+    let columns = Pretty::Array(["id", "name", "created_at"].map(Pretty::from).into());
+    let properties: FieldMap = vec![
+        ("a", Pretty::Text("1".into())),
+        ("columns", columns),
+        ("", Pretty::Elided(2)),
+    ]
+    .into();
+    let scan = Pretty::record("Scan")
+        .field("properties", Pretty::Map(properties))
+        .build();
+    let mut config = PrettyConfig {
+        width: 24,
+        ..Default::default()
+    };
+    let mut out = String::new();
+    config.unicode(&mut out, &scan);
+    assert_eq!(
+        out,
+        "+------------------------+\n\
+         | Scan                   |\n\
+         | └── properties:        |\n\
+         |     ┌── a: 1           |\n\
+         |     ├── columns:       |\n\
+         |     │   ┌── id         |\n\
+         |     │   ├── name       |\n\
+         |     │   └── created_at |\n\
+         |     └── … 2 more       |\n\
+         +------------------------+"
+    );
+    assert_eq!(Pretty::parse_unicode(&out), Ok(scan));
+}
+
+#[test]
+fn notes_are_read_back() {
+    let scan = Pretty::record("Scan")
+        .fields([("table", "t1"), ("filter", "a = 1"), ("limit", "10")])
+        .note("pushed down from Filter")
+        .note("missing statistics")
+        .child(Pretty::record("Source").field("name", "s"))
+        .build();
+    for width in [10, 200] {
+        let mut config = PrettyConfig {
+            width,
+            ..Default::default()
+        };
+        let mut out = String::new();
+        config.unicode(&mut out, &scan);
+        assert_eq!(Pretty::parse_unicode(&out), Ok(scan.clone()), "{}", out);
+    }
+}
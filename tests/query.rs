@@ -0,0 +1,85 @@
+use pretty_xmlish::{query::Query, visit::Step, Pretty};
+
+fn scan(table: &'static str) -> Pretty<'static> {
+    Pretty::record("Scan").field("table", table).build()
+}
+
+/// A join of a scan and a filter, with scans in the values of fields.
+fn plan() -> Pretty<'static> {
+    Pretty::record("Join")
+        .field("type", "Inner")
+        .field("Scan", "a field, not a record")
+        .field("build", scan("t1"))
+        .child(scan("t2"))
+        .child(
+            Pretty::record("Filter")
+                .field("inputs", Pretty::Array(vec![scan("t3"), scan("t4")]))
+                .build(),
+        )
+        .build()
+}
+
+/// The paths of what `query` selects.
+fn select(query: &str) -> Vec<Vec<Step<'static>>> {
+    let plan = plan();
+    let found = plan.select(query).unwrap();
+    found.into_iter().map(|(path, _)| path).collect()
+}
+
+fn field(key: &'static str) -> Step<'static> {
+    Step::Field(key.into())
+}
+
+#[test]
+fn children_and_descendants() {
+    assert_eq!(select("/Join"), [vec![]]);
+    assert_eq!(select("/Scan"), Vec::<Vec<Step>>::new());
+    assert_eq!(
+        select("/Join/Scan"),
+        [vec![field("build")], vec![Step::Child(0)]]
+    );
+    assert_eq!(select("Filter"), [vec![Step::Child(1)]]);
+    assert_eq!(
+        select("//Scan"),
+        [
+            vec![field("build")],
+            vec![Step::Child(0)],
+            vec![Step::Child(1), field("inputs"), Step::Element(0)],
+            vec![Step::Child(1), field("inputs"), Step::Element(1)],
+        ]
+    );
+    assert_eq!(
+        select("Filter//Scan[2]"),
+        [vec![Step::Child(1), field("inputs"), Step::Element(1)]]
+    );
+    assert_eq!(select("/Join/*").len(), 5);
+}
+
+#[test]
+fn fields_and_conditions() {
+    assert_eq!(select("/Join/@Scan"), [vec![field("Scan")]]);
+    assert_eq!(select("//Scan[@table='t3']/@table").len(), 1);
+    assert_eq!(select("//*[@table=\"t1\"]"), [vec![field("build")]]);
+    assert_eq!(select("//*[@inputs]"), [vec![Step::Child(1)]]);
+    assert_eq!(select("//Scan[@table='t9']").len(), 0);
+    let plan = plan();
+    let found = plan.select("/Join/Filter/@inputs").unwrap();
+    assert_eq!(found[0].1, &Pretty::Array(vec![scan("t3"), scan("t4")]));
+}
+
+#[test]
+fn parse_errors() {
+    for (query, pos, message) in [
+        ("", 0, "expected a name"),
+        ("/", 1, "expected a name"),
+        ("/Join[", 6, "expected `@key` or a position"),
+        ("/Join[0]", 6, "expected `@key` or a position"),
+        ("/Join[@a='b]", 10, "unclosed string"),
+        ("/Join[@a=b]", 9, "expected a quoted string"),
+        ("/Join[1", 7, "expected `]`"),
+        ("Join Scan", 4, "expected `/`"),
+    ] {
+        let err = Query::parse(query).unwrap_err();
+        assert_eq!((err.pos, err.message.as_str()), (pos, message), "{}", query);
+    }
+}
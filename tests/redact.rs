@@ -0,0 +1,31 @@
+use pretty_xmlish::{redact::Redaction, Pretty};
+
+#[test]
+fn notes_and_metrics_are_redacted() {
+    let scan = |table| {
+        Pretty::record("Scan")
+            .field("table", table)
+            .note("filter: name = 'alice'")
+            .metric("rows=10")
+    };
+    let plan = Pretty::record("Join")
+        .field("build", scan("t1"))
+        .child(scan("t2"))
+        .build();
+    let redaction = Redaction {
+        keys: vec!["build".into()],
+        ..Redaction::default()
+    }
+    .with_predicate(|v| v.contains("alice"));
+    let masked = |table, metric| {
+        Pretty::record("Scan")
+            .field("table", table)
+            .note("<redacted>")
+            .metric(metric)
+    };
+    let expected = Pretty::record("Join")
+        .field("build", masked("<redacted>", "<redacted>"))
+        .child(masked("t2", "rows=10"))
+        .build();
+    assert_eq!(plan.redact(&redaction), expected);
+}
@@ -0,0 +1,170 @@
+use pretty_xmlish::{Expr, Pretty, PrettyConfig};
+
+fn values() -> Pretty<'static> {
+    let call = Expr::call("least", vec![Expr::lit("$1"), Expr::lit("$2")]);
+    Pretty::record("Values")
+        .field("row", Pretty::tuple(vec!["alpha".into(), "beta".into()]))
+        .field("f", call)
+        .build()
+}
+
+fn config() -> PrettyConfig {
+    PrettyConfig {
+        need_boundaries: false,
+        width: 12,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn broken_tuples_and_calls_keep_their_delimiters() {
+    let mut out = String::new();
+    config().unicode(&mut out, &values());
+    assert_eq!(
+        out,
+        "Values\n\
+         ├── row: (\n\
+         │   ┌── alpha,\n\
+         │   └── beta\n\
+         │   )\n\
+         └── f: least(\n\
+        \x20   ┌── $1,\n\
+        \x20   └── $2\n\
+        \x20   )"
+    );
+
+    let mut out = String::new();
+    config().ascii(&mut out, &values());
+    assert_eq!(
+        out,
+        "Values {\n\
+        \x20   row: (\n\
+        \x20       alpha,\n\
+        \x20       beta\n\
+        \x20   ),\n\
+        \x20   f: least(\n\
+        \x20       $1,\n\
+        \x20       $2\n\
+        \x20   )\n\
+         }"
+    );
+}
+
+#[test]
+fn broken_tuple_at_the_top() {
+    let tuple = Pretty::tuple(vec!["alpha".into(), "beta".into()]);
+    let mut config = PrettyConfig {
+        width: 5,
+        ..config()
+    };
+    let mut out = String::new();
+    config.unicode(&mut out, &tuple);
+    assert_eq!(out, "(\n┌── alpha,\n└── beta\n)");
+}
+
+#[test]
+fn keys_of_broken_arrays_are_in_the_width() {
+    let join = Pretty::record("J")
+        .field(
+            "somewhat_long_key0",
+            Pretty::Array(vec!["= t1 f(a)".into(), "AND x_1".into()]),
+        )
+        .field(
+            "k",
+            Pretty::Map(vec![("another_long_key", Pretty::Ref(81))].into()),
+        )
+        .build();
+    let config = PrettyConfig {
+        width: 39,
+        ..Default::default()
+    };
+    let mut out = String::new();
+    config.clone().ascii(&mut out, &join);
+    assert_eq!(
+        out,
+        "+----------------------------------------+\n\
+         | J {                                    |\n\
+         |     somewhat_long_key0: [              |\n\
+         |         = t1 f(a),                     |\n\
+         |         AND x_1                        |\n\
+         |     ],                                 |\n\
+         |     k: { another_long_key: → see #81 } |\n\
+         | }                                      |\n\
+         +----------------------------------------+"
+    );
+
+    let mut out = String::new();
+    config.clone().unicode(&mut out, &join);
+    assert_eq!(
+        out,
+        "+----------------------------------------+\n\
+         | J                                      |\n\
+         | ├── somewhat_long_key0:                |\n\
+         | │   ┌── = t1 f(a)                      |\n\
+         | │   └── AND x_1                        |\n\
+         | └── k: { another_long_key: → see #81 } |\n\
+         +----------------------------------------+"
+    );
+}
+
+#[test]
+fn empty_and_padded_seqs_in_fields() {
+    let least = Expr::call("least", vec![]).to_pretty();
+    let values = Pretty::record("Values")
+        .field("a_key", least)
+        .field("t", Pretty::tuple(vec![]))
+        .field("f", Pretty::tuple(vec!["alpha".into(), "beta".into()]))
+        .build();
+    let config = PrettyConfig {
+        align_keys: Some(8),
+        ..config()
+    };
+    let mut out = String::new();
+    config.clone().unicode(&mut out, &values);
+    assert_eq!(
+        out,
+        "Values\n\
+         ├── a_key: least()\n\
+         ├── t:     ()\n\
+         └── f:     (\n\
+        \x20   ┌── alpha,\n\
+        \x20   └── beta\n\
+        \x20   )"
+    );
+
+    let mut out = String::new();
+    config.clone().ascii(&mut out, &values);
+    assert_eq!(
+        out,
+        "Values {\n\
+        \x20   a_key: least(),\n\
+        \x20   t:     (),\n\
+        \x20   f:     (\n\
+        \x20       alpha,\n\
+        \x20       beta\n\
+        \x20   )\n\
+         }"
+    );
+}
+
+#[test]
+fn empty_seq_at_the_top() {
+    let mut config = PrettyConfig {
+        width: 4,
+        ..Default::default()
+    };
+    for (seq, expected) in [
+        (Pretty::tuple(vec![]), "+----+\n| () |\n+----+"),
+        (
+            Expr::call("least", vec![]).to_pretty(),
+            "+---------+\n| least() |\n+---------+",
+        ),
+    ] {
+        let mut out = String::new();
+        config.clone().unicode(&mut out, &seq);
+        assert_eq!(out, expected);
+        let mut out = String::new();
+        config.ascii(&mut out, &seq);
+        assert_eq!(out, expected);
+    }
+}
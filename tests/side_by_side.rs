@@ -0,0 +1,92 @@
+use pretty_xmlish::{Pretty, PrettyConfig};
+
+fn scan(table: &'static str) -> Pretty<'static> {
+    Pretty::record("Scan").field("table", table).build()
+}
+
+#[test]
+fn rows_are_aligned() {
+    let left = Pretty::record("Join")
+        .field("type", "Inner")
+        .field("cond", "a = b")
+        .child(scan("t1"))
+        .child(scan("t2"))
+        .build();
+    let right = Pretty::record("Join")
+        .field("type", "Left")
+        .field("limit", "10")
+        .child(scan("t1"))
+        .child(Pretty::record("Filter").child(scan("t2")))
+        .build();
+    let mut config = PrettyConfig {
+        width: 60,
+        ..Default::default()
+    };
+    let mut out = String::new();
+    config.unicode_side_by_side(&mut out, &left, &right);
+    assert_eq!(
+        out,
+        "+-----------------------------------------------------+\n\
+         | Join                   │ Join                       |\n\
+         | ├── type: Inner        │ ├── type: Left             |\n\
+         |                        │ ├── limit: 10              |\n\
+         | ├── cond: a = b        │                            |\n\
+         | ├── Scan { table: t1 } │ ├── Scan { table: t1 }     |\n\
+         | └── Scan { table: t2 } │ └── Filter                 |\n\
+         |                        │     └── Scan { table: t2 } |\n\
+         +-----------------------------------------------------+"
+    );
+}
+
+#[test]
+fn inserted_items_leave_a_blank() {
+    let cols = |cols: &[&'static str]| {
+        let cols = cols.iter().map(|&c| c.into()).collect();
+        Pretty::record("Project")
+            .field("cols", Pretty::Array(cols))
+            .build()
+    };
+    let left = cols(&["alpha", "gamma", "delta"]);
+    let right = cols(&["alpha", "beta", "gamma"]);
+    let mut config = PrettyConfig {
+        width: 30,
+        ..Default::default()
+    };
+    let mut out = String::new();
+    config.unicode_side_by_side(&mut out, &left, &right);
+    assert_eq!(
+        out,
+        "+-------------------------------+\n\
+         | Project       │ Project       |\n\
+         | └── cols:     │ └── cols:     |\n\
+         |     ┌── alpha │     ┌── alpha |\n\
+         |               │     ├── beta  |\n\
+         |     ├── gamma │     └── gamma |\n\
+         |     └── delta │               |\n\
+         +-------------------------------+"
+    );
+}
+
+#[test]
+fn key_lines_are_in_the_column_width() {
+    let left = Pretty::record("Scan")
+        .field(
+            "somewhat_long_key0",
+            Pretty::Map(vec![("k0", Pretty::Ref(81))].into()),
+        )
+        .build();
+    let mut config = PrettyConfig {
+        width: 75,
+        ..Default::default()
+    };
+    let mut out = String::new();
+    config.unicode_side_by_side(&mut out, &left, &scan("t1"));
+    assert_eq!(
+        out,
+        "+-----------------------------------------------+\n\
+         | Scan                     │ Scan { table: t1 } |\n\
+         | └── somewhat_long_key0:  │                    |\n\
+         |     ┌── k0: → see #81    │                    |\n\
+         +-----------------------------------------------+"
+    );
+}
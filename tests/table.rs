@@ -0,0 +1,177 @@
+use pretty_xmlish::{Elision, Pretty, PrettyConfig};
+
+fn values() -> Pretty<'static> {
+    let rows = ["1", "3"].map(|a| Pretty::record("r").field("a", a).field("bb", "2").build());
+    Pretty::record("Values")
+        .field("rows", Pretty::Array(rows.into()))
+        .field("n", "2")
+        .build()
+}
+
+#[test]
+fn separator_after_a_grid() {
+    let mut config = PrettyConfig {
+        need_boundaries: false,
+        width: 10,
+        tables: true,
+        ..Default::default()
+    };
+    let mut out = String::new();
+    config.ascii(&mut out, &values());
+    assert_eq!(
+        out,
+        "Values {\n\
+        \x20   rows:\n\
+        \x20       +---+----+\n\
+        \x20       | a | bb |\n\
+        \x20       +---+----+\n\
+        \x20       | 1 |  2 |\n\
+        \x20       | 3 |  2 |\n\
+        \x20       +---+----+\n\
+        \x20   ,\n\
+        \x20   n: 2\n\
+         }"
+    );
+}
+
+fn numbers(n: usize) -> Pretty<'static> {
+    let rows = [7, 14, 100, 3, 5][..n].iter().map(|&a| {
+        Pretty::record("r")
+            .field("a", Pretty::int(a))
+            .field("bb", "x")
+            .build()
+    });
+    Pretty::Array(rows.collect())
+}
+
+fn tables() -> PrettyConfig {
+    PrettyConfig {
+        need_boundaries: false,
+        width: 10,
+        tables: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn grid_in_place_of_a_child() {
+    let values = Pretty::record("V")
+        .child(numbers(2))
+        .child(Pretty::record("Scan").build())
+        .build();
+    let mut out = String::new();
+    tables().unicode(&mut out, &values);
+    assert_eq!(
+        out,
+        "V\n\
+         ├── ┌────┬────┐\n\
+         │   │ a  │ bb │\n\
+         │   ├────┼────┤\n\
+         │   │  7 │ x  │\n\
+         │   │ 14 │ x  │\n\
+         │   └────┴────┘\n\
+         └── Scan"
+    );
+
+    let mut out = String::new();
+    tables().ascii(&mut out, &values);
+    assert_eq!(
+        out,
+        "V {\n\
+         }\n\
+        \x20   +----+----+\n\
+        \x20   | a  | bb |\n\
+        \x20   +----+----+\n\
+        \x20   |  7 | x  |\n\
+        \x20   | 14 | x  |\n\
+        \x20   +----+----+\n\
+        \x20   Scan"
+    );
+}
+
+#[test]
+fn grids_in_an_array() {
+    let values = Pretty::record("V")
+        .field("all", Pretty::Array(vec![numbers(2), numbers(3)]))
+        .build();
+    let mut out = String::new();
+    tables().unicode(&mut out, &values);
+    assert_eq!(
+        out,
+        "V\n\
+         └── all:\n\
+        \x20   ┌── ┌────┬────┐\n\
+        \x20   │   │ a  │ bb │\n\
+        \x20   │   ├────┼────┤\n\
+        \x20   │   │  7 │ x  │\n\
+        \x20   │   │ 14 │ x  │\n\
+        \x20   │   └────┴────┘\n\
+        \x20   └── ┌─────┬────┐\n\
+        \x20       │ a   │ bb │\n\
+        \x20       ├─────┼────┤\n\
+        \x20       │   7 │ x  │\n\
+        \x20       │  14 │ x  │\n\
+        \x20       │ 100 │ x  │\n\
+        \x20       └─────┴────┘"
+    );
+
+    let mut out = String::new();
+    tables().ascii(&mut out, &values);
+    assert_eq!(
+        out,
+        "V {\n\
+        \x20   all: [\n\
+        \x20       +----+----+\n\
+        \x20       | a  | bb |\n\
+        \x20       +----+----+\n\
+        \x20       |  7 | x  |\n\
+        \x20       | 14 | x  |\n\
+        \x20       +----+----+\n\
+        \x20       ,\n\
+        \x20       +-----+----+\n\
+        \x20       | a   | bb |\n\
+        \x20       +-----+----+\n\
+        \x20       |   7 | x  |\n\
+        \x20       |  14 | x  |\n\
+        \x20       | 100 | x  |\n\
+        \x20       +-----+----+\n\
+        \x20   ]\n\
+         }"
+    );
+}
+
+#[test]
+fn grid_at_the_top_with_elided_rows() {
+    let mut config = PrettyConfig {
+        elision: Elision {
+            max_elements: Some(1),
+            ..Default::default()
+        },
+        ..tables()
+    };
+    let mut out = String::new();
+    config.clone().ascii(&mut out, &numbers(5));
+    assert_eq!(
+        out,
+        "+----------+----+\n\
+         | a        | bb |\n\
+         +----------+----+\n\
+         |        7 | x  |\n\
+         | … 3 more |    |\n\
+         |        5 | x  |\n\
+         +----------+----+"
+    );
+
+    let mut out = String::new();
+    config.unicode(&mut out, &numbers(5));
+    assert_eq!(
+        out,
+        "┌──────────┬────┐\n\
+         │ a        │ bb │\n\
+         ├──────────┼────┤\n\
+         │        7 │ x  │\n\
+         │ … 3 more │    │\n\
+         │        5 │ x  │\n\
+         └──────────┴────┘"
+    );
+}
@@ -0,0 +1,92 @@
+#![cfg(feature = "testing")]
+
+use std::{fs, panic, path::PathBuf};
+
+use pretty_xmlish::{
+    testing::{assert_snapshot, snapshot_config, BLESS},
+    Pretty,
+};
+
+fn scan(table: &'static str) -> Pretty<'static> {
+    Pretty::record("Scan")
+        .field("table", table)
+        .field("rows", "10")
+        .build()
+}
+
+/// The message of the panic of `f`, if any.
+fn panic_message(f: impl FnOnce() + panic::UnwindSafe) -> Option<String> {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let res = panic::catch_unwind(f);
+    panic::set_hook(hook);
+    let err = res.err()?;
+    let message = match err.downcast::<String>() {
+        Ok(s) => *s,
+        Err(err) => err.downcast_ref::<&str>().unwrap().to_string(),
+    };
+    Some(message)
+}
+
+// One test, as the environment variable is shared by the threads
+#[test]
+fn missing_blessed_and_mismatching_snapshots() {
+    let dir = std::env::temp_dir().join(format!("pretty-xmlish-{}", std::process::id()));
+    let check = |pretty: Pretty<'static>| {
+        let dir: PathBuf = dir.clone();
+        move || assert_snapshot(&dir, "scan", &pretty, snapshot_config())
+    };
+
+    let missing = panic_message(check(scan("t1"))).unwrap();
+    assert!(missing.starts_with("missing snapshot"), "{}", missing);
+    assert!(!dir.join("scan.txt").exists());
+
+    std::env::set_var(BLESS, "1");
+    let blessed = panic_message(check(scan("t1")));
+    std::env::remove_var(BLESS);
+    assert_eq!(blessed, None);
+    let mut expected = String::new();
+    snapshot_config().unicode(&mut expected, &scan("t1"));
+    assert_eq!(
+        fs::read_to_string(dir.join("scan.txt")).unwrap(),
+        expected + "\n"
+    );
+    assert_eq!(panic_message(check(scan("t1"))), None);
+
+    // The diff is as wide as the snapshot, not narrowed by the rendering
+    let mismatch = panic_message(check(scan("t2"))).unwrap();
+    assert!(mismatch.starts_with("snapshot"), "{}", mismatch);
+    assert!(
+        mismatch.ends_with(":\n~ Scan { ~ table: t1 → t2, rows: 10 }"),
+        "{}",
+        mismatch
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn scalars_are_compared_as_printed() {
+    let dir = std::env::temp_dir().join(format!("pretty-xmlish-scalars-{}", std::process::id()));
+    let agg = |rows| {
+        Pretty::record("Agg")
+            .field("rows", Pretty::int(rows))
+            .field("exact", Pretty::bool(true))
+            .field("ratio", Pretty::float(0.5))
+            .build()
+    };
+    let mut old = String::new();
+    snapshot_config().unicode(&mut old, &agg(10));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("agg.txt"), old + "\n").unwrap();
+
+    let new = agg(11);
+    let mismatch = panic_message(|| assert_snapshot(&dir, "agg", &new, snapshot_config()));
+    fs::remove_dir_all(&dir).unwrap();
+    let mismatch = mismatch.unwrap();
+    assert!(
+        mismatch.ends_with(":\n~ Agg { ~ rows: 10 → 11, exact: true, ratio: 0.5 }"),
+        "{}",
+        mismatch
+    );
+}
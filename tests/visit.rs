@@ -0,0 +1,130 @@
+use pretty_xmlish::{
+    visit::{Step, Visitor, VisitorMut},
+    Pretty,
+};
+
+fn plan() -> Pretty<'static> {
+    Pretty::record("Join")
+        .field("on", Pretty::Array(vec!["a".into(), "b".into()]))
+        .child(Pretty::record("Scan").field("table", "t1").build())
+        .child(Pretty::record("Scan").field("password", "hunter2").build())
+        .build()
+}
+
+/// A short description of a tree, for the orders.
+fn label(pretty: &Pretty) -> String {
+    match pretty {
+        Pretty::Record(xml) => xml.name.to_string(),
+        Pretty::Array(_) => "[]".into(),
+        p => p.to_one_line_string(false),
+    }
+}
+
+#[test]
+fn pre_and_post_order() {
+    let plan = plan();
+    let pre: Vec<_> = plan.pre_order().map(|(_, p)| label(p)).collect();
+    assert_eq!(
+        pre,
+        ["Join", "[]", "a", "b", "Scan", "t1", "Scan", "hunter2"]
+    );
+    let post: Vec<_> = plan.post_order().map(|(_, p)| label(p)).collect();
+    assert_eq!(
+        post,
+        ["a", "b", "[]", "t1", "Scan", "hunter2", "Scan", "Join"]
+    );
+
+    let paths: Vec<_> = plan.pre_order().map(|(path, _)| path).collect();
+    let on = Step::Field("on".into());
+    assert_eq!(
+        paths[..4],
+        [
+            vec![],
+            vec![on.clone()],
+            vec![on.clone(), Step::Element(0)],
+            vec![on, Step::Element(1)],
+        ]
+    );
+    assert_eq!(paths[7], [Step::Child(1), Step::Field("password".into())]);
+}
+
+/// Records the calls, and does not enter the arrays.
+#[derive(Default)]
+struct Trace(Vec<String>);
+
+impl<'a> Visitor<'a> for Trace {
+    fn enter(&mut self, path: &[Step<'a>], pretty: &Pretty<'a>) -> bool {
+        self.0
+            .push(format!("enter {} at {}", label(pretty), path.len()));
+        !matches!(pretty, Pretty::Array(_))
+    }
+
+    fn leave(&mut self, _path: &[Step<'a>], pretty: &Pretty<'a>) {
+        self.0.push(format!("leave {}", label(pretty)));
+    }
+}
+
+#[test]
+fn walk_skips_subtrees() {
+    let mut trace = Trace::default();
+    Pretty::record("Join")
+        .field("on", Pretty::Array(vec!["a".into()]))
+        .child(Pretty::record("Scan").build())
+        .build()
+        .walk(&mut trace);
+    assert_eq!(
+        trace.0,
+        [
+            "enter Join at 0",
+            "enter [] at 1",
+            "leave []",
+            "enter Scan at 1",
+            "leave Scan",
+            "leave Join",
+        ]
+    );
+}
+
+/// Hides the values of the fields with a secret key.
+struct Redact;
+
+impl<'a> VisitorMut<'a> for Redact {
+    fn enter(&mut self, path: &[Step<'a>], pretty: &mut Pretty<'a>) -> bool {
+        if let Some(Step::Field(key)) = path.last() {
+            if key == "password" {
+                *pretty = "***".into();
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[test]
+fn walk_mut_redacts() {
+    let mut plan = plan();
+    plan.walk_mut(&mut Redact);
+    let expected = Pretty::record("Join")
+        .field("on", Pretty::Array(vec!["a".into(), "b".into()]))
+        .child(Pretty::record("Scan").field("table", "t1").build())
+        .child(Pretty::record("Scan").field("password", "***").build())
+        .build();
+    assert_eq!(plan, expected);
+}
+
+#[test]
+fn map_records_renames() {
+    let renamed = plan().map_records(|mut xml| {
+        if xml.name == "Scan" {
+            xml.name = "TableScan".into();
+        }
+        xml
+    });
+    let names: Vec<_> = renamed.pre_order().filter_map(|(_, p)| p.name()).collect();
+    assert_eq!(names, ["Join", "TableScan", "TableScan"]);
+
+    let records = plan().fold(&mut |p, subs: Vec<usize>| {
+        subs.into_iter().sum::<usize>() + matches!(p, Pretty::Record(_)) as usize
+    });
+    assert_eq!(records, 3);
+}
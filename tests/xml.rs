@@ -0,0 +1,53 @@
+use pretty_xmlish::{Pretty, PrettyConfig, WeightStyle};
+
+fn xml(config: &PrettyConfig, pretty: &Pretty) -> String {
+    let mut out = String::new();
+    config.xml(&mut out, pretty);
+    out
+}
+
+fn one_line() -> PrettyConfig {
+    PrettyConfig {
+        indent: 0,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn comments_have_no_double_dash() {
+    let scan = Pretty::record("Scan").note("a---b--").build();
+    assert_eq!(xml(&one_line(), &scan), "<Scan><!-- a- - -b- - --></Scan>");
+}
+
+#[test]
+fn attributes_are_unique() {
+    let scan = Pretty::record("Scan")
+        .id(1)
+        .field("id", "t1")
+        .field("a b", "x")
+        .field("a_b", "y")
+        .build();
+    assert_eq!(
+        xml(&one_line(), &scan),
+        r#"<Scan id="1" a_b="x"><id>t1</id><a_b>y</a_b></Scan>"#
+    );
+    assert!(Pretty::parse_xml(&xml(&one_line(), &scan)).is_ok());
+}
+
+#[test]
+fn no_escape_sequences() {
+    let scan = Pretty::record("Scan")
+        .field("table", "t\u{1}1\r")
+        .note("no statistics")
+        .weight(1.0)
+        .build();
+    let config = PrettyConfig {
+        colors: true,
+        weights: WeightStyle::Percent,
+        ..one_line()
+    };
+    assert_eq!(
+        xml(&config, &scan),
+        r#"<Scan table="t�1&#13;"><!-- no statistics --></Scan>"#
+    );
+}